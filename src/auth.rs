@@ -0,0 +1,77 @@
+use std::collections::HashMap;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::{Mutex, OnceLock};
+
+/// Tokens found for each forge CLI and host, so nothing is asked twice in a run.
+static TOKENS: OnceLock<Mutex<HashMap<String, Option<String>>>> = OnceLock::new();
+
+/// An API token for the forge at `host`, from the first of: the environment variables in `vars` paired with `host`,
+/// the forge's `cli` (such as `gh auth token --hostname`), and git's credential helpers for `host`.
+///
+/// Each variable is only read for the host it belongs to, so a github.com token is never sent anywhere else.
+pub fn token(host: &str, vars: &[(&str, &str)], cli: &[&str]) -> Option<String> {
+    //
+    let tokens = TOKENS.get_or_init(Mutex::default);
+    let key = format!("{}@{host}", cli.first().unwrap_or(&""));
+
+    if let Some(token) = tokens.lock().ok()?.get(&key) {
+        return token.clone();
+    }
+
+    let token = vars
+        .iter()
+        .filter(|(_, owner)| owner.eq_ignore_ascii_case(host))
+        .find_map(|(name, _)| std::env::var(name).ok().filter(|token| !token.is_empty()))
+        .or_else(|| from_cli(cli))
+        .or_else(|| from_credential_helper(host));
+
+    tokens.lock().ok()?.insert(key, token.clone());
+
+    token
+}
+
+/// The token a forge CLI prints, when it is installed and logged in.
+fn from_cli(cli: &[&str]) -> Option<String> {
+    //
+    let (program, args) = cli.split_first()?;
+    let output = Command::new(program).args(args).stderr(Stdio::null()).output().ok()?;
+    let token = String::from_utf8(output.stdout).ok()?.trim().to_string();
+
+    (output.status.success() && !token.is_empty()).then_some(token)
+}
+
+/// The password git's credential helpers hold for `host`, without ever prompting for one.
+fn from_credential_helper(host: &str) -> Option<String> {
+    //
+    let mut child = Command::new("git")
+        .args(["-c", "core.askPass=", "credential", "fill"])
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .env("GCM_INTERACTIVE", "never")
+        .env_remove("GIT_ASKPASS")
+        .env_remove("SSH_ASKPASS")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+
+    child
+        .stdin
+        .take()?
+        .write_all(format!("protocol=https\nhost={host}\n\n").as_bytes())
+        .ok()?;
+
+    let output = child.wait_with_output().ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    String::from_utf8(output.stdout)
+        .ok()?
+        .lines()
+        .find_map(|line| line.strip_prefix("password="))
+        .filter(|password| !password.is_empty())
+        .map(ToString::to_string)
+}
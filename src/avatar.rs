@@ -0,0 +1,310 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Write;
+use std::io::Cursor;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use anyhow::Result;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use colored::{Color, Colorize};
+use image::{DynamicImage, ImageFormat, RgbaImage};
+use sha2::{Digest, Sha256};
+
+use crate::commit::Commit;
+use crate::forge::{self, Forge};
+use crate::{cache, http};
+
+/// Terminal columns an avatar takes up, whether drawn or spelled out as initials.
+pub const WIDTH: usize = 2;
+
+/// Size avatars are downloaded at, and scaled down from by the terminal.
+const PIXELS: u32 = 64;
+
+/// How long a downloaded avatar is used before it is fetched again.
+const CACHE_TTL: Duration = Duration::from_hours(7 * 24);
+
+/// Size of sixel avatars, which are drawn pixel for pixel: two sixel bands, shorter than any line.
+const SIXEL_PIXELS: u32 = 12;
+
+/// A terminal graphics protocol avatars can be drawn with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Graphics {
+    /// kitty's graphics protocol, also spoken by Ghostty.
+    Kitty,
+    /// iTerm2's inline images, also shown by WezTerm.
+    Iterm,
+    /// DEC sixels, for foot, mlterm, Konsole and Windows Terminal.
+    Sixel,
+}
+
+impl Graphics {
+    /// Guess the protocol the terminal speaks from its environment, as asking it would mean reading its reply.
+    #[must_use]
+    pub fn detect() -> Option<Graphics> {
+        //
+        let var = |name| std::env::var(name).unwrap_or_default();
+        let term = var("TERM");
+
+        // Multiplexers don't pass images through.
+        if !var("TMUX").is_empty() || term.starts_with("screen") {
+            return None;
+        }
+
+        if term == "xterm-kitty" || !var("KITTY_WINDOW_ID").is_empty() || var("TERM_PROGRAM") == "ghostty" {
+            Some(Graphics::Kitty)
+        } else if matches!(var("TERM_PROGRAM").as_str(), "iTerm.app" | "WezTerm") || var("LC_TERMINAL") == "iTerm2" {
+            Some(Graphics::Iterm)
+        } else if term.starts_with("foot")
+            || term.starts_with("mlterm")
+            || term.contains("sixel")
+            || !var("KONSOLE_VERSION").is_empty()
+            || !var("WT_SESSION").is_empty()
+        {
+            Some(Graphics::Sixel)
+        } else {
+            None
+        }
+    }
+}
+
+/// The protocol avatars are drawn with; without one, authors are shown by their initials.
+static GRAPHICS: OnceLock<Graphics> = OnceLock::new();
+
+/// Draw avatars with `graphics` for the rest of the run.
+pub fn set_graphics(graphics: Graphics) {
+    let _ = GRAPHICS.set(graphics);
+}
+
+/// Drawn avatars by author email, or `None` for authors without one.
+static AVATARS: OnceLock<Mutex<HashMap<String, Option<String>>>> = OnceLock::new();
+
+/// Set once a download has failed, so the rest of the run falls back to initials without waiting on the network.
+static FETCH_FAILED: AtomicBool = AtomicBool::new(false);
+
+/// The commit author's avatar, drawn with the terminal's graphics protocol, or else their initials.
+#[must_use]
+pub fn avatar(c: &Commit) -> String {
+    //
+    GRAPHICS
+        .get()
+        .and_then(|graphics| drawn(c, *graphics))
+        .unwrap_or_else(|| initials(&c.author_name, &c.author_email))
+}
+
+/// The author's avatar drawn as escape codes, downloaded once per author.
+fn drawn(c: &Commit, graphics: Graphics) -> Option<String> {
+    //
+    let avatars = AVATARS.get_or_init(Mutex::default);
+
+    if let Some(found) = avatars.lock().ok()?.get(&c.author_email) {
+        return found.clone();
+    }
+
+    let found = download(c).and_then(|bytes| draw(&bytes, graphics).ok());
+
+    avatars.lock().ok()?.insert(c.author_email.clone(), found.clone());
+
+    found
+}
+
+/// The author's avatar from their forge profile or Gravatar, whichever has one first.
+///
+/// Avatars, and their absence, are cached for a week by the hash of the author's email.
+fn download(c: &Commit) -> Option<Vec<u8>> {
+    //
+    let hash = email_hash(&c.author_email);
+
+    if let Some((bytes, age)) = cache::read("avatars", &hash)
+        && age < CACHE_TTL
+    {
+        return (!bytes.is_empty()).then_some(bytes);
+    }
+
+    for url in sources(c, &hash) {
+        if FETCH_FAILED.load(Ordering::Relaxed) || http::offline() {
+            return None;
+        }
+
+        match http::get_bytes(&url) {
+            Ok(Some(bytes)) => {
+                cache::write("avatars", &hash, &bytes);
+                return Some(bytes);
+            }
+            Ok(None) => {}
+            Err(_) => FETCH_FAILED.store(true, Ordering::Relaxed),
+        }
+    }
+
+    if !FETCH_FAILED.load(Ordering::Relaxed) && !http::offline() {
+        cache::write("avatars", &hash, &[]);
+    }
+
+    None
+}
+
+/// The SHA-256 of a normalized email address, as Gravatar identifies people by.
+fn email_hash(email: &str) -> String {
+    Sha256::digest(email.trim().to_lowercase())
+        .iter()
+        .fold(String::new(), |mut hash, byte| {
+            let _ = write!(hash, "{byte:02x}");
+            hash
+        })
+}
+
+/// Where the author's avatar may be found: their GitHub profile picture when their login is known, then Gravatar.
+fn sources(c: &Commit, hash: &str) -> Vec<String> {
+    //
+    let github = c
+        .url
+        .as_deref()
+        .filter(|url| Forge::detect(url) == Forge::GitHub)
+        .and_then(|url| forge::profile_url(url, c.login()?))
+        .map(|profile| format!("{profile}.png?size={PIXELS}"));
+
+    github
+        .into_iter()
+        .chain([format!("https://gravatar.com/avatar/{hash}?s={PIXELS}&d=404")])
+        .collect()
+}
+
+/// An image file drawn as the escape codes of a graphics protocol, in a space `WIDTH` columns wide.
+fn draw(bytes: &[u8], graphics: Graphics) -> Result<String> {
+    //
+    let image = image::load_from_memory(bytes)?;
+
+    Ok(match graphics {
+        Graphics::Kitty => kitty(&png(&image.thumbnail(PIXELS, PIXELS))?),
+        Graphics::Iterm => iterm(&png(&image.thumbnail(PIXELS, PIXELS))?),
+        Graphics::Sixel => sixel(&image.thumbnail(SIXEL_PIXELS, SIXEL_PIXELS).to_rgba8()),
+    })
+}
+
+/// An image encoded as PNG, the one format every protocol accepts.
+fn png(image: &DynamicImage) -> Result<Vec<u8>> {
+    //
+    let mut png = Cursor::new(Vec::new());
+
+    image.write_to(&mut png, ImageFormat::Png)?;
+
+    Ok(png.into_inner())
+}
+
+/// A kitty graphics command placing a PNG over `WIDTH` cells, sent in the 4096 byte chunks the protocol requires.
+fn kitty(png: &[u8]) -> String {
+    //
+    let data = STANDARD.encode(png);
+    let chunks: Vec<_> = data.as_bytes().chunks(4096).collect();
+    let mut escapes = String::new();
+
+    for (i, chunk) in chunks.iter().enumerate() {
+        let chunk = String::from_utf8_lossy(chunk);
+        let more = u8::from(i + 1 < chunks.len());
+
+        // q=2 keeps the terminal from replying on stdin.
+        if i == 0 {
+            let _ = write!(escapes, "\x1B_Gf=100,a=T,q=2,c={WIDTH},r=1,m={more};{chunk}\x1B\\");
+        } else {
+            let _ = write!(escapes, "\x1B_Gm={more};{chunk}\x1B\\");
+        }
+    }
+
+    escapes
+}
+
+/// An iTerm2 inline image sized to `WIDTH` cells.
+fn iterm(png: &[u8]) -> String {
+    format!(
+        "\x1B]1337;File=inline=1;width={WIDTH};height=1;preserveAspectRatio=1:{}\x07",
+        STANDARD.encode(png)
+    )
+}
+
+/// A sixel image in a 6×6×6 color cube, which fits the registers of any sixel terminal.
+///
+/// Mode 8452 leaves the cursor to the right of the image rather than beneath it, for the rest of the line.
+fn sixel(image: &RgbaImage) -> String {
+    //
+    let (width, height) = image.dimensions();
+
+    let register = |x, y| {
+        let pixel = image.get_pixel(x, y);
+        let level = |channel: u8| u32::from(channel) * 5 / 255;
+
+        (pixel[3] >= 128).then(|| level(pixel[0]) * 36 + level(pixel[1]) * 6 + level(pixel[2]))
+    };
+
+    let mut escapes = format!("\x1B[?8452h\x1BP0;1;0q\"1;1;{width};{height}");
+
+    let used: BTreeSet<_> = (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .filter_map(|(x, y)| register(x, y))
+        .collect();
+
+    for color in &used {
+        let percent = |level: u32| level * 100 / 5;
+        let _ = write!(
+            escapes,
+            "#{color};2;{};{};{}",
+            percent(color / 36),
+            percent(color / 6 % 6),
+            percent(color % 6)
+        );
+    }
+
+    for band in (0..height).step_by(6) {
+        let rows = band..(band + 6).min(height);
+
+        for color in &used {
+            let columns: String = (0..width)
+                .map(|x| {
+                    let bits = rows
+                        .clone()
+                        .filter(|&y| register(x, y) == Some(*color))
+                        .fold(0_u8, |bits, y| bits | 1 << (y - band));
+
+                    char::from(63 + bits)
+                })
+                .collect();
+
+            if columns.chars().any(|sixel| sixel != '?') {
+                let _ = write!(escapes, "#{color}{columns}$");
+            }
+        }
+
+        escapes.push('-');
+    }
+
+    escapes.push_str("\x1B\\\x1B[?8452l");
+    escapes
+}
+
+/// Up to two initials of the author's name, in a color of their own chosen by their email.
+fn initials(name: &str, email: &str) -> String {
+    //
+    const COLORS: [Color; 6] = [
+        Color::Red,
+        Color::Green,
+        Color::Yellow,
+        Color::Blue,
+        Color::Magenta,
+        Color::Cyan,
+    ];
+
+    let mut initials: String = name
+        .split_whitespace()
+        .filter_map(|word| word.chars().next())
+        .take(2)
+        .flat_map(char::to_uppercase)
+        .collect();
+
+    if initials.is_empty() {
+        initials = email.chars().take(1).flat_map(char::to_uppercase).collect();
+    }
+
+    let color = COLORS[email.bytes().map(usize::from).sum::<usize>() % COLORS.len()];
+
+    format!("{initials:<WIDTH$}").color(color).bold().to_string()
+}
@@ -0,0 +1,84 @@
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+/// Set by `--no-cache`, so cached answers are ignored, though fresh ones are still written.
+static BYPASS: AtomicBool = AtomicBool::new(false);
+
+/// Ignore what is cached for the rest of the run.
+pub fn set_bypass(bypass: bool) {
+    BYPASS.store(bypass, Ordering::Relaxed);
+}
+
+/// `$XDG_CACHE_HOME/devmoji-log`, defaulting to `~/.cache`.
+pub fn dir() -> Option<PathBuf> {
+    //
+    let base = std::env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::home_dir().map(|home| home.join(".cache")))?;
+
+    Some(base.join("devmoji-log"))
+}
+
+/// The bytes cached under `kind`, such as `checks`, and `key`, with how long ago they were written.
+pub fn read(kind: &str, key: &str) -> Option<(Vec<u8>, Duration)> {
+    //
+    if BYPASS.load(Ordering::Relaxed) {
+        return None;
+    }
+
+    let file = dir()?.join(kind).join(key);
+    let age = std::fs::metadata(&file)
+        .ok()?
+        .modified()
+        .ok()?
+        .elapsed()
+        .unwrap_or_default();
+
+    Some((std::fs::read(&file).ok()?, age))
+}
+
+/// Cache bytes under `kind` and `key`; a cache that can't be written is only slower.
+pub fn write(kind: &str, key: &str, bytes: &[u8]) {
+    //
+    let Some(dir) = dir().map(|dir| dir.join(kind)) else {
+        return;
+    };
+
+    if std::fs::create_dir_all(&dir).is_ok() {
+        let _ = std::fs::write(dir.join(key), bytes);
+    }
+}
+
+/// A value cached as JSON, with its age.
+pub fn get<T: DeserializeOwned>(kind: &str, key: &str) -> Option<(T, Duration)> {
+    //
+    let (bytes, age) = read(kind, &format!("{key}.json"))?;
+
+    Some((serde_json::from_slice(&bytes).ok()?, age))
+}
+
+/// Cache a value as JSON.
+pub fn put<T: Serialize>(kind: &str, key: &str, value: &T) {
+    if let Ok(json) = serde_json::to_vec(value) {
+        write(kind, &format!("{key}.json"), &json);
+    }
+}
+
+/// Delete everything cached, returning the directory removed, if there was one.
+pub fn clear() -> Result<Option<PathBuf>> {
+    //
+    let Some(dir) = dir() else {
+        return Ok(None);
+    };
+
+    match std::fs::remove_dir_all(&dir) {
+        Ok(()) => Ok(Some(dir)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e).with_context(|| format!("Removing {}", dir.display())),
+    }
+}
@@ -1,5 +1,9 @@
-use std::collections::HashSet;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
 use std::string::ToString;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 use anyhow::Result;
 use colored::Colorize;
@@ -8,186 +12,2042 @@ use git_conventional::Commit as ConventionalCommit;
 use jiff::fmt::friendly::SpanPrinter;
 use jiff::tz::{Offset, TimeZone};
 use jiff::{SpanRound, Timestamp, Unit, Zoned};
+use regex::Regex;
+use serde::{Serialize, Serializer};
 
+use crate::emoji::{self, EmojiList, EmojiSet, EmojiStyle, commit_emoji};
+use crate::forge::{self, CheckStatus, Enrichment, PullRequest};
+use crate::tags;
+
+/// Returned when HEAD points at a branch without any commits, such as right after `git init`.
+#[derive(Debug)]
+pub struct UnbornHead(pub String);
+
+impl std::fmt::Display for UnbornHead {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "No commits yet on {}", self.0)
+    }
+}
+
+impl std::error::Error for UnbornHead {}
+
+/// Selection criteria applied while walking the commit history.
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Clone)]
+pub struct Query {
+    pub types: Vec<String>,
+    pub scopes: Vec<Regex>,
+    pub breaking_only: bool,
+    pub bots: Vec<Regex>,
+    pub signatures: bool,
+    pub date_source: DateSource,
+    pub trailers: Vec<TrailerFilter>,
+    pub count: usize,
+    pub skip: usize,
+    pub range: Option<String>,
+    pub branch: Option<String>,
+    pub not: Vec<String>,
+    pub branch_point: Option<String>,
+    pub all: bool,
+    pub since: Option<Zoned>,
+    pub until: Option<Zoned>,
+    pub author: Option<Regex>,
+    pub grep: Option<Regex>,
+    pub paths: Vec<String>,
+    /// A monorepo package's path, whose commits are those touching it or scoped to its name.
+    pub package: Option<String>,
+    pub no_merges: bool,
+    pub merges_only: bool,
+    pub first_parent: bool,
+    pub unreleased: bool,
+    pub releases: bool,
+    pub remote: Option<String>,
+    /// Length commit hashes are abbreviated to, instead of git's `core.abbrev`.
+    pub abbrev: Option<usize>,
+    pub stats: bool,
+    /// Forge API to look up each commit's pull request in, with `--enrich`.
+    pub enrich: Option<Enrichment>,
+    pub checks: bool,
+    /// Look up authors' forge logins, for `--show-author` with `--enrich`.
+    pub logins: bool,
+}
+
+impl Query {
+    /// The same filters starting from HEAD, without revisions and paths that only make sense in the original checkout.
+    #[must_use]
+    pub fn at_head(&self) -> Query {
+        Query {
+            range: None,
+            branch: None,
+            not: Vec::new(),
+            branch_point: None,
+            all: false,
+            unreleased: false,
+            paths: Vec::new(),
+            package: None,
+            ..self.clone()
+        }
+    }
+
+    /// Whether a commit passes every filter of the query.
+    fn matches(&self, commit: &Commit) -> bool {
+        self.contains(&commit.timestamp)
+            && self.matches_author(commit)
+            && self.matches_message(commit)
+            && self.matches_merge(commit)
+            && self.matches_type(commit)
+            && self.matches_scope(commit)
+            && (!self.breaking_only || commit.is_breaking())
+            && !self.bots.iter().any(|pattern| pattern.is_match(&commit.author()))
+            && self.trailers.iter().all(|trailer| trailer.matches(commit))
+    }
+
+    /// Whether the conventional commit scope matches one of the `--scope` globs; unscoped commits never match.
+    fn matches_scope(&self, commit: &Commit) -> bool {
+        self.scopes.is_empty()
+            || commit
+                .conventional
+                .as_ref()
+                .and_then(|cc| cc.scope.as_deref())
+                .is_some_and(|scope| self.scopes.iter().any(|pattern| pattern.is_match(scope)))
+    }
+
+    /// Whether the conventional commit type is one of `--type`; non-conventional commits never match.
+    fn matches_type(&self, commit: &Commit) -> bool {
+        self.types.is_empty()
+            || commit.conventional.as_ref().is_some_and(|cc| {
+                self.types
+                    .iter()
+                    .any(|kind| kind.eq_ignore_ascii_case(&cc.kind) || kind.eq_ignore_ascii_case(cc.canonical_kind()))
+            })
+    }
+
+    /// Whether the commit survives `--no-merges` / `--merges-only`.
+    fn matches_merge(&self, commit: &Commit) -> bool {
+        if commit.is_merge() {
+            !self.no_merges
+        } else {
+            !self.merges_only
+        }
+    }
+
+    /// Whether a commit timestamp falls within the `since` / `until` bounds.
+    fn contains(&self, timestamp: &Zoned) -> bool {
+        self.since.as_ref().is_none_or(|since| timestamp >= since)
+            && self.until.as_ref().is_none_or(|until| timestamp <= until)
+    }
+
+    /// Whether the message matches the `--grep` pattern.
+    fn matches_message(&self, commit: &Commit) -> bool {
+        self.grep
+            .as_ref()
+            .is_none_or(|pattern| pattern.is_match(&commit.message))
+    }
+
+    /// Whether the author matches the `--author` pattern, tested against "Name <email>" like `git log`.
+    fn matches_author(&self, commit: &Commit) -> bool {
+        self.author
+            .as_ref()
+            .is_none_or(|pattern| pattern.is_match(&commit.author()))
+    }
+}
+
+#[derive(Serialize)]
 pub struct Commit {
+    #[serde(rename = "hash", serialize_with = "serialize_display")]
+    pub oid: git2::Oid,
+    #[serde(rename = "short_hash")]
     pub id: String,
     pub message: String,
+    #[serde(flatten)]
+    pub conventional: Option<Conventional>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub trailers: Vec<(String, String)>,
+    #[serde(serialize_with = "serialize_timestamp")]
     pub timestamp: Zoned,
-    pub url: String,
+    #[serde(skip)]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub branches: Vec<String>,
+    pub author_name: String,
+    pub author_email: String,
+    #[serde(rename = "parents")]
+    pub parent_count: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub release: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repo: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<SignatureStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stats: Option<DiffStats>,
+    /// Files changed within the queried paths, when paths were given.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<PathBuf>,
+    /// The pull request the commit was merged through, looked up with `--enrich`.
+    #[serde(rename = "pull_request", skip_serializing_if = "Option::is_none")]
+    pub pull: Option<PullRequest>,
+    /// The combined status of the commit's CI checks, looked up with `--checks`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checks: Option<CheckStatus>,
+    /// The author's forge login, looked up with `--show-author` and `--enrich`.
+    #[serde(rename = "author_login", skip_serializing_if = "Option::is_none")]
+    pub login: Option<String>,
+    /// The reflog action that moved HEAD onto the commit, such as `checkout`, in `reflog` listings.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub action: Option<String>,
+}
+
+/// Size of a commit's change against its first parent.
+#[derive(Clone, Copy, Debug, Serialize)]
+pub struct DiffStats {
+    pub files: usize,
+    pub insertions: usize,
+    pub deletions: usize,
+}
+
+/// How `Commit::autolink` writes links.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Markup {
+    /// OSC-8 hyperlinks, when enabled.
+    Terminal,
+    /// `[#123](url)` links.
+    Markdown,
+    /// `<a href="url">#123</a>` links, in text that is already escaped.
+    Html,
+}
+
+impl Markup {
+    /// `text` linked to `url`.
+    #[must_use]
+    pub fn link(self, url: &str, text: &str) -> String {
+        match self {
+            Markup::Terminal => hyperlink(url, text),
+            Markup::Markdown => format!("[{text}]({url})"),
+            Markup::Html => format!(r#"<a href="{url}">{text}</a>"#),
+        }
+    }
+}
+
+/// Which of a commit's timestamps to show and filter on; they diverge once commits are rebased or amended.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum DateSource {
+    /// When the change was originally written.
+    Author,
+    /// When the commit was last (re)written.
+    #[default]
+    Committer,
+}
+
+/// Whether a signed commit's GPG or SSH signature could be verified.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SignatureStatus {
+    Verified,
+    Unverified,
+}
+
+/// A `--trailer key[=value]` filter; keys compare case-insensitively and values as case-insensitive substrings.
+#[derive(Clone, Debug)]
+pub struct TrailerFilter {
+    pub key: String,
+    pub value: Option<String>,
+}
+
+impl TrailerFilter {
+    pub fn parse(input: &str) -> Result<Self, String> {
+        //
+        let (key, value) = match input.split_once('=') {
+            Some((key, value)) => (key.trim(), Some(value.trim().to_lowercase())),
+            None => (input.trim(), None),
+        };
+
+        if key.is_empty() {
+            return Err(format!("Missing trailer key in {input:?}"));
+        }
+
+        Ok(TrailerFilter {
+            key: key.to_string(),
+            value,
+        })
+    }
+
+    fn matches(&self, commit: &Commit) -> bool {
+        commit.trailers.iter().any(|(key, value)| {
+            key.eq_ignore_ascii_case(&self.key)
+                && self
+                    .value
+                    .as_ref()
+                    .is_none_or(|wanted| value.to_lowercase().contains(wanted))
+        })
+    }
+}
+
+/// The parsed header of a conventional commit message.
+#[derive(Clone, Serialize)]
+pub struct Conventional {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub scope: Option<String>,
+    pub breaking: bool,
+    pub description: String,
+    /// Emoji and `:shortcodes:` written into the description, such as by a devmoji hook, moved out of it.
+    #[serde(skip)]
+    pub emoji: Vec<&'static str>,
+}
+
+impl Conventional {
+    #[must_use]
+    pub fn parse(message: &str) -> Option<Self> {
+        //
+        let cc = ConventionalCommit::parse(message).ok()?;
+        let (emoji, description) = emoji::extract(strip_pull_request(cc.description()));
+
+        Some(Conventional {
+            kind: cc.type_().to_string(),
+            scope: cc.scope().map(|scope| scope.to_string()),
+            breaking: cc.breaking(),
+            description,
+            emoji,
+        })
+    }
+
+    /// The part before the description, such as `feat(api)!`.
+    #[must_use]
+    pub fn header(&self) -> String {
+        //
+        let scope = self
+            .scope
+            .as_ref()
+            .map(|scope| format!("({scope})"))
+            .unwrap_or_default();
+        let bang = if self.breaking { "!" } else { "" };
+
+        format!("{}{scope}{bang}", self.kind)
+    }
+
+    /// Emoji for the type, scope, breaking marker and any emoji moved out of the description.
+    #[must_use]
+    pub fn emoji(&self) -> String {
+        emoji::styled(&Commit::format_emoji(
+            self.canonical_kind(),
+            self.scope.as_deref(),
+            &self.emoji,
+            self.breaking,
+        ))
+    }
+
+    /// Emoji for the type, scope and breaking marker alone, as Unicode whatever the output style.
+    #[must_use]
+    pub fn header_emoji(&self) -> String {
+        Commit::format_emoji(self.canonical_kind(), self.scope.as_deref(), &[], self.breaking)
+    }
+
+    /// The subject line with emoji and without colors, such as `feat(api)!: 💥 ✨ new endpoint`.
+    #[must_use]
+    pub fn title(&self) -> String {
+        //
+        let emoji = self.emoji();
+
+        if emoji.is_empty() {
+            format!("{}: {}", self.header(), self.description)
+        } else {
+            format!("{}: {emoji} {}", self.header(), self.description)
+        }
+    }
+
+    /// The subject line with emoji and a highlighted header, or a colored badge in place of both with `--no-emoji`.
+    #[must_use]
+    pub fn highlighted(&self) -> String {
+        //
+        if emoji::emoji_style() == EmojiStyle::Badges {
+            return format!("{} {}", self.badge(), self.description);
+        }
+
+        let mut header = self.kind.clone();
+
+        if let Some(scope_str) = &self.scope {
+            let _ = write!(header, "({})", scope_str.bold());
+        }
+
+        if self.breaking {
+            header.push('!');
+        }
+
+        let header = format!("{header}:").blue();
+        let emoji = self.emoji();
+
+        if emoji.is_empty() {
+            format!("{header} {}", self.description)
+        } else {
+            format!("{header} {emoji} {}", self.description)
+        }
+    }
+
+    /// A colored text badge standing in for emoji, such as `[feat(api)!]`.
+    fn badge(&self) -> String {
+        //
+        let badge = format!("[{}]", self.header());
+
+        if self.breaking {
+            badge.red().bold().to_string()
+        } else {
+            badge.blue().to_string()
+        }
+    }
+
+    /// The type this commit's type is an alias of, or the type itself.
+    #[must_use]
+    pub fn canonical_kind(&self) -> &str {
+        TYPE_ALIASES
+            .get()
+            .and_then(|aliases| aliases.get(&self.kind))
+            .map_or(&self.kind, String::as_str)
+    }
+}
+
+impl Commit {
+    #[must_use]
+    pub fn id(&self) -> String {
+        match self.link() {
+            Some(link) => hyperlink(&link, &self.id),
+            None => self.id.cyan().to_string(),
+        }
+    }
+
+    /// Web URL of the commit, when the repository has a remote.
+    #[must_use]
+    pub fn link(&self) -> Option<String> {
+        self.url
+            .as_ref()
+            .map(|url| forge::commit_url(url, &self.oid.to_string(), &self.id))
+    }
+
+    /// Web URL of an issue in the commit's repository.
+    #[must_use]
+    pub fn issue_link(&self, number: u64) -> Option<String> {
+        self.url.as_ref().and_then(|url| forge::issue_url(url, number))
+    }
+
+    /// Web URL of a pull request in the commit's repository.
+    #[must_use]
+    pub fn pull_request_link(&self, number: u64) -> Option<String> {
+        self.url.as_ref().and_then(|url| forge::pull_request_url(url, number))
+    }
+
+    /// The author's forge login, as looked up or as given away by a noreply address.
+    #[must_use]
+    pub fn login(&self) -> Option<&str> {
+        self.login.as_deref().or_else(|| forge::email_login(&self.author_email))
+    }
+
+    /// Web URL of the author's profile on the forge, when their login is known.
+    #[must_use]
+    pub fn author_link(&self) -> Option<String> {
+        forge::profile_url(self.url.as_ref()?, self.login()?)
+    }
+
+    /// 🔀 for a pull request merge and 📦 for a squash merge, linked to the pull request.
+    #[must_use]
+    pub fn pull_request_mark(&self, markup: Markup) -> String {
+        //
+        let Some(number) = self.pull_request() else {
+            return String::new();
+        };
+
+        let mark = if self.is_merge() { "🔀" } else { "📦" };
+
+        match self.pull_request_link(number) {
+            Some(link) => format!("{} ", markup.link(&link, mark)),
+            None => format!("{mark} "),
+        }
+    }
+
+    /// Issue references such as `#123` and `GH-123` in `text` turned into links to the repository's issues.
+    ///
+    /// The pull request a squash merge names, as in "Add login (#456)", links to the pull request instead.
+    /// Issue tracker keys such as `PROJ-1234` are linked too, when a `[tracker]` is configured.
+    #[must_use]
+    pub fn autolink(&self, text: &str, markup: Markup) -> String {
+        //
+        static ISSUE: OnceLock<Regex> = OnceLock::new();
+
+        let text = link_tracker_keys(text, markup);
+
+        if self.url.is_none() {
+            return text;
+        }
+
+        let issue = ISSUE.get_or_init(|| Regex::new(r"(^|[\s(\[])((?:#|GH-)(\d+))\b").expect("valid regex"));
+
+        issue
+            .replace_all(&text, |caps: &regex::Captures<'_>| {
+                let reference = &caps[2];
+                let number = caps[3].parse().ok();
+                let link = if reference.starts_with('#') && number == self.pull_request() {
+                    number.and_then(|number| self.pull_request_link(number))
+                } else {
+                    number.and_then(|number| self.issue_link(number))
+                };
+
+                let Some(link) = link else {
+                    return caps[0].to_string();
+                };
+
+                format!("{}{}", &caps[1], markup.link(&link, reference))
+            })
+            .into_owned()
+    }
+
+    /// Build from a git commit, canonicalising the author through the repository's `.mailmap` when given.
+    ///
+    /// The hash is abbreviated to `abbrev` characters, or by git's `core.abbrev` setting when `None`.
+    pub fn new(
+        commit: &git2::Commit<'_>,
+        url: Option<&str>,
+        mailmap: Option<&git2::Mailmap>,
+        date_source: DateSource,
+        abbrev: Option<usize>,
+    ) -> Self {
+        //
+        let author = mailmap
+            .and_then(|mailmap| commit.author_with_mailmap(mailmap).ok())
+            .unwrap_or_else(|| commit.author());
+
+        let id = match abbrev {
+            Some(length) => commit.id().to_string().chars().take(length.max(4)).collect(),
+            None => commit
+                .as_object()
+                .short_id()
+                .ok()
+                .and_then(|buf| buf.as_str().map(ToString::to_string))
+                .unwrap_or_default(),
+        };
+
+        Commit {
+            oid: commit.id(),
+            id,
+            message: commit.message().unwrap_or_default().to_string(),
+            conventional: Conventional::parse(commit.message().unwrap_or_default()),
+            trailers: parse_trailers(commit.message().unwrap_or_default()),
+            timestamp: match date_source {
+                DateSource::Author => zoned_from_time(&commit.author().when()),
+                DateSource::Committer => zoned_from_time(&commit.time()),
+            },
+            url: url.map(ToString::to_string),
+            branches: Vec::new(),
+            author_name: author.name().unwrap_or_default().to_string(),
+            author_email: author.email().unwrap_or_default().to_string(),
+            parent_count: commit.parent_count(),
+            release: None,
+            repo: None,
+            signature: None,
+            stats: None,
+            files: Vec::new(),
+            pull: None,
+            checks: None,
+            login: None,
+            action: None,
+        }
+    }
+
+    /// Walk the repository containing `path`, returning the commits selected by the query.
+    pub fn query(path: &Path, query: &Query) -> Result<Vec<Commit>> {
+        //
+        let mut commits = Vec::new();
+
+        Self::walk(path, query, |commit| {
+            commits.push(commit);
+            Ok(())
+        })?;
+
+        Ok(commits)
+    }
+
+    /// Walk the repository containing `path`, handing each selected commit to `emit` as soon as it is found.
+    pub fn walk(path: &Path, query: &Query, mut emit: impl FnMut(Commit) -> Result<()>) -> Result<()> {
+        //
+        // Outside of a repository there is simply no activity to show.
+        let Ok(repo) = git2::Repository::discover(path) else {
+            return Ok(());
+        };
+
+        // Without a remote, commits are still listed but without hyperlinks.
+        let url = remote_url(&repo, query.remote.as_deref())?;
+
+        // A freshly initialised repository, or an orphan branch, has no HEAD commit to start from.
+        if query.range.is_none()
+            && query.branch.is_none()
+            && !query.all
+            && let Some(branch) = unborn_branch(&repo)
+        {
+            return Err(UnbornHead(branch).into());
+        }
+
+        let mut revwalk = repo.revwalk()?;
+
+        push_revisions(&repo, &mut revwalk, query)?;
+
+        for base in &query.not {
+            revwalk.hide(repo.revparse_single(base)?.peel_to_commit()?.id())?;
+        }
+        revwalk.set_sorting(git2::Sort::TIME)?;
+
+        if query.first_parent {
+            revwalk.simplify_first_parent()?;
+        }
+
+        let tips = if query.all { branch_tips(&repo)? } else { Vec::new() };
+        let mailmap = repo.mailmap().ok();
+        let pathspecs = repo_pathspecs(&repo, path, &query.paths);
+        let package = query
+            .package
+            .as_deref()
+            .map(|package| Package::new(&repo, path, package));
+        let releases = if query.releases {
+            tags::releases(&repo)?
+        } else {
+            Vec::new()
+        };
+
+        let commits = revwalk
+            .filter_map(|oid_result| oid_result.ok().and_then(|oid| repo.find_commit(oid).ok()))
+            .filter(|commit| touches_paths(&repo, commit, &pathspecs))
+            .filter(|commit| package.as_ref().is_none_or(|package| package.contains(&repo, commit)))
+            .map(|commit| {
+                Commit::new(
+                    &commit,
+                    url.as_deref(),
+                    mailmap.as_ref(),
+                    query.date_source,
+                    query.abbrev,
+                )
+            })
+            .filter(|commit| query.matches(commit))
+            .skip(query.skip)
+            .take(query.count);
+
+        for mut commit in commits {
+            commit.branches = containing_branches(&repo, &tips, commit.oid);
+            commit.release = tags::containing_release(&repo, &releases, commit.oid).map(|release| release.name.clone());
+
+            if query.signatures {
+                commit.signature = signature_status(&repo, commit.oid);
+            }
+
+            if query.stats {
+                commit.stats = diff_stats(&repo, commit.oid);
+            }
+
+            if let (Some(enrichment), Some(url)) = (query.enrich, &commit.url) {
+                commit.pull = forge::pull_request(enrichment, url, &commit.oid.to_string(), commit.pull_request());
+            }
+
+            if query.logins
+                && let (Some(enrichment), Some(url)) = (query.enrich, &commit.url)
+                && forge::email_login(&commit.author_email).is_none()
+            {
+                commit.login = forge::author_login(enrichment, url, &commit.oid.to_string(), &commit.author_email);
+            }
+
+            if query.checks
+                && let Some(url) = &commit.url
+            {
+                commit.checks = forge::checks(url, &commit.oid.to_string());
+            }
+
+            if !pathspecs.is_empty() {
+                commit.files = changed_files(&repo, commit.oid, &pathspecs);
+            }
+
+            emit(commit)?;
+        }
+
+        Ok(())
+    }
+
+    /// Interleave the activity of several repositories into one timeline, labelling each commit with its repository.
+    pub fn query_all(paths: &[PathBuf], query: &Query) -> Result<Vec<Commit>> {
+        //
+        if let [path] = paths {
+            return Self::query(path, query);
+        }
+
+        // Pagination applies to the merged timeline, so each repository has to supply the skipped commits too.
+        let per_repo = Query {
+            count: query.count.saturating_add(query.skip),
+            skip: 0,
+            ..query.clone()
+        };
+
+        let mut commits = Vec::new();
+
+        for path in paths {
+            let label = repo_label(path);
+
+            let found = match Self::query(path, &per_repo) {
+                Err(e) if e.is::<UnbornHead>() => continue,
+                found => found?,
+            };
+
+            commits.extend(found.into_iter().map(|mut commit| {
+                commit.repo = Some(label.clone());
+                commit
+            }));
+        }
+
+        commits.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+
+        Ok(commits.into_iter().skip(query.skip).take(query.count).collect())
+    }
+
+    /// The author in "Name <email>" form.
+    #[must_use]
+    pub fn author(&self) -> String {
+        format!("{} <{}>", self.author_name, self.author_email)
+    }
+
+    /// Whether the commit is marked breaking, with either `!` or a `BREAKING CHANGE:` footer.
+    #[must_use]
+    pub fn is_breaking(&self) -> bool {
+        self.conventional.as_ref().is_some_and(|cc| cc.breaking)
+    }
+
+    /// The migration notes of a `BREAKING CHANGE:` footer, if the commit has one.
+    #[must_use]
+    pub fn breaking_change(&self) -> Option<&str> {
+        self.trailers
+            .iter()
+            .find(|(key, _)| key == "BREAKING CHANGE" || key == "BREAKING-CHANGE")
+            .map(|(_, value)| value.trim())
+    }
+
+    #[must_use]
+    pub fn is_merge(&self) -> bool {
+        self.parent_count > 1
+    }
+
+    /// Whether the commit should follow Conventional Commits but doesn't; merges, gitmoji subjects and reflog details
+    /// such as "moving to HEAD~1" are exempt.
+    #[must_use]
+    pub fn is_nonconforming(&self) -> bool {
+        self.conventional.is_none()
+            && !self.is_merge()
+            && gitmoji(&self.message).is_none()
+            && self.action.as_deref().is_none_or(|action| action.starts_with("commit"))
+    }
+
+    /// Why the message doesn't parse as a conventional commit, if it doesn't.
+    #[must_use]
+    pub fn parse_error(&self) -> Option<String> {
+        ConventionalCommit::parse(&self.message).err().map(|e| e.to_string())
+    }
+
+    /// Everything after the subject line: the body and footers.
+    #[must_use]
+    pub fn body(&self) -> &str {
+        self.message.split_once('\n').map_or("", |(_, body)| body.trim())
+    }
+
+    /// Pull request number, from a squash-merge subject ending in "(#123)" or a "Merge pull request #123" merge.
+    ///
+    /// GitLab's merge commits name their merge request on a "See merge request group/project!123" line instead.
+    #[must_use]
+    pub fn pull_request(&self) -> Option<u64> {
+        //
+        if let Some(line) = self.message.lines().find(|line| line.starts_with("See merge request ")) {
+            return line.trim_end().rsplit_once('!')?.1.parse().ok();
+        }
+
+        let subject = self.message.lines().next().unwrap_or_default().trim_end();
+
+        let number = match subject.strip_prefix("Merge pull request #") {
+            Some(rest) => rest.split_whitespace().next()?,
+            None => subject.strip_suffix(')')?.rsplit_once("(#")?.1,
+        };
+
+        number.parse().ok()
+    }
+
+    /// Branch names containing this commit, in the style of `git log --decorate`.
+    #[must_use]
+    pub fn decoration(&self) -> String {
+        //
+        if self.branches.is_empty() {
+            return String::new();
+        }
+
+        format!("({}) ", self.branches.join(", ")).yellow().to_string()
+    }
+
+    /// The reflog action in the form of: "checkout: ", dimmed, for `reflog` listings.
+    #[must_use]
+    pub fn action_label(&self) -> String {
+        self.action
+            .as_ref()
+            .map(|action| format!("{action}: ").dimmed().to_string())
+            .unwrap_or_default()
+    }
+
+    /// 🔏 for a verified signature, ❓ for one that couldn't be verified, and nothing for unsigned commits.
+    #[must_use]
+    pub fn signature_mark(&self) -> &'static str {
+        match self.signature {
+            Some(SignatureStatus::Verified) => "🔏 ",
+            Some(SignatureStatus::Unverified) => "❓ ",
+            None => "",
+        }
+    }
+
+    /// ✅, 🟡 or ❌ for the commit's CI checks, and nothing when they weren't looked up or there are none.
+    #[must_use]
+    pub fn checks_mark(&self) -> String {
+        self.checks
+            .map(|checks| format!("{} ", checks.emoji()))
+            .unwrap_or_default()
+    }
+
+    /// Diff stats in the form of: "4 files, +120 −35", when they were collected.
+    #[must_use]
+    pub fn stat(&self) -> Option<String> {
+        //
+        let stats = self.stats?;
+        let plural = if stats.files == 1 { "" } else { "s" };
+
+        Some(format!(
+            "{} file{plural}, {} {}",
+            stats.files,
+            format!("+{}", stats.insertions).green(),
+            format!("−{}", stats.deletions).red()
+        ))
+    }
+
+    /// A compact `++++--` bar of insertions and deletions, growing logarithmically with the churn.
+    #[must_use]
+    pub fn stat_graph(&self) -> Option<String> {
+        //
+        let stats = self.stats?;
+        let churn = stats.insertions + stats.deletions;
+
+        if churn == 0 {
+            return None;
+        }
+
+        // One column for a single changed line, up to ten for a thousand or more.
+        let length = churn.bit_width().min(10) as usize;
+
+        // Round to the nearest column, but never hide insertions or deletions entirely.
+        let plus = ((length * stats.insertions + churn / 2) / churn)
+            .max(usize::from(stats.insertions > 0))
+            .min(length - usize::from(stats.deletions > 0 && length > 1));
+
+        Some(format!(
+            "{}{}",
+            "+".repeat(plus).green(),
+            "-".repeat(length - plus).red()
+        ))
+    }
+
+    /// Repository label shown when aggregating several repositories.
+    #[must_use]
+    pub fn label(&self) -> String {
+        self.repo
+            .as_ref()
+            .map(|repo| format!("[{repo}] ").magenta().to_string())
+            .unwrap_or_default()
+    }
+
+    /// Emoji in a fixed order: breaking first, then the type, the scope and any emoji from the description.
+    fn format_emoji(type_str: &str, scope: Option<&str>, other: &[&'static str], breaking: bool) -> String {
+        let mut emojis = EmojiList::default();
+
+        // Add breaking change emoji if needed
+        if breaking && let Some(emoji) = commit_emoji("breaking") {
+            emojis.insert(emoji);
+        }
+
+        if let Some(emoji) = commit_emoji(type_str).or_else(emoji::fallback_emoji) {
+            emojis.insert(emoji);
+        }
+
+        if let Some(scope_str) = scope {
+            //
+            // Try combined type-scope emoji
+            let combined = format!("{type_str}-{scope_str}");
+
+            if let Some(emoji) = commit_emoji(&combined) {
+                emojis.insert(emoji);
+                //
+            } else if let Some(g) = get_by_shortcode(&combined) {
+                emojis.insert(g.as_str());
+                //
+            } else if let Some(g) = emoji::scope_emoji(scope_str) {
+                emojis.insert(g);
+            }
+        }
+
+        // Add other emojis if present
+        for emoji in other {
+            emojis.insert(emoji);
+        }
+
+        emojis.join(" ")
+    }
+
+    pub fn format(&self, now: &Zoned, printer: &SpanPrinter) -> Result<String> {
+        //
+        let mut formatted = highlight(self.conventional.as_ref(), self.message.trim());
+
+        let _ = write!(formatted, " ({} ago)", self.age(now, printer)?);
+
+        Ok(formatted)
+    }
+
+    /// Emoji for the conventional type, scope, breaking marker and any emoji or `:shortcodes:` in the description.
+    #[must_use]
+    pub fn emoji(&self) -> String {
+        //
+        match &self.conventional {
+            Some(cc) => cc.emoji(),
+            None => emoji::styled(gitmoji(&self.message).map(|(emoji, _)| emoji).unwrap_or_default()),
+        }
+    }
+
+    /// Time elapsed since the commit, in the form of: "1 year, 4 months, 28 days, 18 hours"
+    pub fn age(&self, now: &Zoned, printer: &SpanPrinter) -> Result<String> {
+        //
+        let span = (now - &self.timestamp).round(
+            SpanRound::new()
+                .largest(Unit::Year)
+                .smallest(Unit::Hour)
+                .relative(&self.timestamp),
+        )?;
+
+        Ok(printer.span_to_string(&span))
+    }
+}
+
+/// Name of the branch HEAD points at when that branch has no commits yet.
+fn unborn_branch(repo: &git2::Repository) -> Option<String> {
+    //
+    match repo.head() {
+        Err(e) if e.code() == git2::ErrorCode::UnbornBranch => {
+            let head = repo.find_reference("HEAD").ok()?;
+            let target = head.symbolic_target()?;
+
+            Some(target.strip_prefix("refs/heads/").unwrap_or(target).to_string())
+        }
+        _ => None,
+    }
+}
+
+/// Seed the revwalk from a revision or range (`v1.2.0..HEAD`, `main...feature`), a branch, or HEAD.
+fn push_revisions(repo: &git2::Repository, revwalk: &mut git2::Revwalk<'_>, query: &Query) -> Result<(), git2::Error> {
+    //
+    if query.all {
+        // A detached HEAD isn't on any branch, but is still part of "all" activity.
+        if repo.head_detached()? {
+            revwalk.push_head()?;
+        }
+
+        return revwalk.push_glob("refs/heads/*");
+    }
+
+    let Some(spec) = query.range.as_deref() else {
+        let start = match query.branch.as_deref() {
+            Some(branch) => repo.resolve_reference_from_short_name(branch)?.peel_to_commit()?.id(),
+            None => repo.head()?.peel_to_commit()?.id(),
+        };
+
+        revwalk.push(start)?;
+
+        if query.unreleased
+            && let Some(release) = tags::latest_release(repo, start)?
+        {
+            revwalk.hide(release.oid)?;
+        }
+
+        if let Some(base) = query.branch_point.as_deref() {
+            let base = repo.revparse_single(base)?.peel_to_commit()?.id();
+
+            revwalk.hide(repo.merge_base(start, base)?)?;
+        }
+
+        return Ok(());
+    };
+
+    let revspec = repo.revparse(spec)?;
+
+    if revspec.mode().contains(git2::RevparseMode::SINGLE) {
+        if let Some(from) = revspec.from() {
+            revwalk.push(from.id())?;
+        }
+
+        return Ok(());
+    }
+
+    // An omitted side of the range (`v1.2.0..`) means HEAD, as with `git log`.
+    let head = || {
+        repo.head()
+            .and_then(|head| head.peel(git2::ObjectType::Commit))
+            .map(|obj| obj.id())
+    };
+    let from = revspec.from().map_or_else(head, |obj| Ok(obj.id()))?;
+    let to = revspec.to().map_or_else(head, |obj| Ok(obj.id()))?;
+
+    revwalk.push(to)?;
+    revwalk.hide(from)?;
+
+    // The symmetric difference also includes commits only reachable from the left side.
+    if revspec.mode().contains(git2::RevparseMode::MERGE_BASE) {
+        revwalk.push(from)?;
+        revwalk.hide(repo.merge_base(from, to)?)?;
+    }
+
+    Ok(())
+}
+
+/// Relative paths and working directories of the initialised submodules of the repository containing `path`.
+pub fn submodules(path: &Path) -> Result<Vec<(String, PathBuf)>> {
+    //
+    let Ok(repo) = git2::Repository::discover(path) else {
+        return Ok(Vec::new());
+    };
+
+    Ok(repo
+        .submodules()?
+        .iter()
+        .filter_map(|submodule| {
+            let workdir = submodule.open().ok()?.workdir()?.to_path_buf();
+
+            Some((submodule.path().display().to_string(), workdir))
+        })
+        .collect())
+}
+
+/// Working directories and checked-out branches of the main worktree and every linked worktree.
+pub fn worktrees(path: &Path) -> Result<Vec<(PathBuf, String)>> {
+    //
+    let Ok(repo) = git2::Repository::discover(path) else {
+        return Ok(Vec::new());
+    };
+
+    // Linked worktrees share the main repository's common directory.
+    let main = git2::Repository::open(repo.commondir())?;
+    let mut checkouts = Vec::new();
+
+    if let Some(workdir) = main.workdir() {
+        checkouts.push((workdir.components().collect(), head_name(&main)));
+    }
+
+    for name in main.worktrees()?.iter().flatten() {
+        let worktree = main.find_worktree(name)?;
+
+        if let Ok(linked) = git2::Repository::open_from_worktree(&worktree) {
+            checkouts.push((worktree.path().to_path_buf(), head_name(&linked)));
+        }
+    }
+
+    Ok(checkouts)
+}
+
+/// The checked-out branch of a repository, for display.
+fn head_name(repo: &git2::Repository) -> String {
+    //
+    if repo.head_detached().unwrap_or_default() {
+        return "detached HEAD".to_string();
+    }
+
+    match repo.head() {
+        Ok(head) => head.shorthand().unwrap_or("HEAD").to_string(),
+        Err(_) => unborn_branch(repo).unwrap_or_else(|| "HEAD".to_string()),
+    }
+}
+
+/// Number of files with staged or unstaged changes in the working tree containing `path`.
+pub fn dirty_files(path: &Path) -> Result<usize> {
+    //
+    let Ok(repo) = git2::Repository::discover(path) else {
+        return Ok(0);
+    };
+
+    if repo.is_bare() {
+        return Ok(0);
+    }
+
+    let mut opts = git2::StatusOptions::new();
+    opts.include_untracked(false).include_ignored(false);
+
+    Ok(repo.statuses(Some(&mut opts))?.len())
 }
 
-impl Commit {
-    #[must_use]
-    pub fn id(&self) -> String {
-        hyperlink(&format!("{}/commit/{}", &self.url, &self.id), &self.id)
-    }
-
-    pub fn last_n_commits(n: usize) -> Result<Vec<Commit>> {
-        //
-        let commits = git2::Repository::discover(std::env::current_dir()?)
-            .and_then(|repo| {
-                let remote = repo.find_remote("origin")?;
-                let url = remote.url().unwrap_or_default().to_string();
-
-                let mut revwalk = repo.revwalk()?;
-
-                revwalk.push_head()?;
-                revwalk.set_sorting(git2::Sort::TIME)?;
-
-                Ok(revwalk
-                    .filter_map(|oid_result| oid_result.ok().and_then(|oid| repo.find_commit(oid).ok()))
-                    .take(n)
-                    .map(|commit| Commit {
-                        id: commit
-                            .as_object()
-                            .short_id()
-                            .ok()
-                            .and_then(|buf| buf.as_str().map(ToString::to_string))
-                            .unwrap_or_default(),
-                        message: commit.message().unwrap_or_default().to_string(),
-                        timestamp: zoned_from_time(&commit.time()),
-                        url: url.clone(),
-                    })
-                    .collect())
-            })
-            .unwrap_or_default();
+/// The `user.email` git commits as in the repository containing `path`, if one is set.
+pub fn user_email(path: &Path) -> Result<Option<String>> {
+    //
+    let repo = git2::Repository::discover(path)?;
+    let email = repo.config()?.get_string("user.email").ok();
 
-        Ok(commits)
+    Ok(email.filter(|email| !email.is_empty()))
+}
+
+/// Short name for the repository containing `path`, taken from its working directory.
+fn repo_label(path: &Path) -> String {
+    git2::Repository::discover(path)
+        .ok()
+        .and_then(|repo| {
+            repo.workdir()?
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+        })
+        .unwrap_or_else(|| path.display().to_string())
+}
+
+/// Web URL of the requested remote, or else the first of `origin`, `upstream`, or any other remote that has one.
+///
+/// Remote lookups apply `url.<base>.insteadOf` rewrites from git config, so aliases such as `git@github-work:`
+/// resolve to the host they stand for before the web URL is built.
+pub fn remote_url(repo: &git2::Repository, name: Option<&str>) -> Result<Option<String>, git2::Error> {
+    //
+    if let Some(name) = name {
+        return Ok(repo.find_remote(name)?.url().and_then(forge::web_url));
     }
 
-    fn format_emoji(type_str: &str, scope: Option<&str>, other: Option<&str>, breaking: bool) -> String {
-        let mut emojis: HashSet<String> = HashSet::new();
+    let remotes = repo.remotes()?;
 
-        // Add breaking change emoji if needed
-        if breaking {
-            get_by_shortcode("boom").map(|g| emojis.insert(g.as_str().to_string()));
+    Ok(["origin", "upstream"]
+        .into_iter()
+        .chain(remotes.iter().flatten())
+        .find_map(|name| repo.find_remote(name).ok()?.url().map(ToString::to_string))
+        .and_then(|url| forge::web_url(&url)))
+}
+
+/// Names and tip commits of every local branch.
+fn branch_tips(repo: &git2::Repository) -> Result<Vec<(String, git2::Oid)>, git2::Error> {
+    //
+    let mut tips = Vec::new();
+
+    for branch in repo.branches(Some(git2::BranchType::Local))? {
+        let (branch, _) = branch?;
+
+        if let (Some(name), Some(oid)) = (branch.name()?, branch.get().target()) {
+            tips.push((name.to_string(), oid));
         }
+    }
+
+    Ok(tips)
+}
+
+/// Key / value trailers at the end of a commit message.
+fn parse_trailers(message: &str) -> Vec<(String, String)> {
+    //
+    // Conventional footers also accept `BREAKING CHANGE:` lines, which make git reject the whole trailer block.
+    if let Ok(cc) = ConventionalCommit::parse(message)
+        && !cc.footers().is_empty()
+    {
+        return cc
+            .footers()
+            .iter()
+            .map(|footer| (footer.token().to_string(), footer.value().to_string()))
+            .collect();
+    }
+
+    git2::message_trailers_strs(message)
+        .map(|trailers| {
+            trailers
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Signature status of a commit, verified through `git verify-commit` so `gpg.*` and SSH signer settings apply.
+fn signature_status(repo: &git2::Repository, oid: git2::Oid) -> Option<SignatureStatus> {
+    //
+    repo.extract_signature(&oid, None).ok()?;
+
+    let verified = std::process::Command::new("git")
+        .arg("--git-dir")
+        .arg(repo.path())
+        .args(["verify-commit", &oid.to_string()])
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success());
+
+    Some(if verified {
+        SignatureStatus::Verified
+    } else {
+        SignatureStatus::Unverified
+    })
+}
+
+/// Files changed, insertions and deletions of a commit against its first parent, or the empty tree for a root commit.
+fn diff_stats(repo: &git2::Repository, oid: git2::Oid) -> Option<DiffStats> {
+    //
+    let commit = repo.find_commit(oid).ok()?;
+    let parent = commit.parent(0).ok().and_then(|parent| parent.tree().ok());
+    let diff = repo
+        .diff_tree_to_tree(parent.as_ref(), Some(&commit.tree().ok()?), None)
+        .ok()?;
+    let stats = diff.stats().ok()?;
+
+    Some(DiffStats {
+        files: stats.files_changed(),
+        insertions: stats.insertions(),
+        deletions: stats.deletions(),
+    })
+}
+
+/// Names of the branches whose tip is, or descends from, the given commit.
+fn containing_branches(repo: &git2::Repository, tips: &[(String, git2::Oid)], oid: git2::Oid) -> Vec<String> {
+    tips.iter()
+        .filter(|(_, tip)| *tip == oid || repo.graph_descendant_of(*tip, oid).unwrap_or_default())
+        .map(|(name, _)| name.clone())
+        .collect()
+}
+
+/// Make pathspecs given relative to the starting directory relative to the repository root, as `git log` does.
+fn repo_pathspecs(repo: &git2::Repository, start: &Path, paths: &[String]) -> Vec<String> {
+    //
+    let start = start.canonicalize().unwrap_or_else(|_| start.to_path_buf());
+    let prefix = repo
+        .workdir()
+        .and_then(|workdir| start.strip_prefix(workdir).ok())
+        .unwrap_or_else(|| Path::new(""));
+
+    paths
+        .iter()
+        .map(|path| prefix.join(path).to_string_lossy().trim_end_matches('/').to_string())
+        .collect()
+}
+
+/// A package of a monorepo: a subtree, and the scope its commits are given, which is the subtree's name or path.
+struct Package {
+    pathspecs: Vec<String>,
+    path: String,
+}
 
-        if let Some(emoji) = commit_emoji(type_str) {
-            emojis.insert(emoji.to_string());
+impl Package {
+    fn new(repo: &git2::Repository, start: &Path, path: &str) -> Self {
+        Package {
+            pathspecs: repo_pathspecs(repo, start, &[path.to_string()]),
+            path: path.trim_matches('/').to_string(),
         }
+    }
 
-        if let Some(scope_str) = scope {
-            //
-            // Try combined type-scope emoji
-            if let Some(g) = get_by_shortcode(&format!("{type_str}-{scope_str}")) {
-                emojis.insert(g.as_str().to_string());
-                //
-            } else if let Some(g) = commit_emoji(scope_str) {
-                emojis.insert(g.to_string());
-            }
+    /// Whether a commit is scoped to the package, or else changes files in its subtree.
+    fn contains(&self, repo: &git2::Repository, commit: &git2::Commit<'_>) -> bool {
+        //
+        let name = self.path.rsplit('/').next().unwrap_or_default();
+        let scoped = commit
+            .message()
+            .and_then(Conventional::parse)
+            .and_then(|cc| cc.scope)
+            .is_some_and(|scope| scope.eq_ignore_ascii_case(name) || scope.eq_ignore_ascii_case(&self.path));
+
+        scoped || touches_paths(repo, commit, &self.pathspecs)
+    }
+}
+
+/// Absolute paths of the files matching the pathspecs that a commit changes against its first parent.
+fn changed_files(repo: &git2::Repository, oid: git2::Oid, pathspecs: &[String]) -> Vec<PathBuf> {
+    //
+    let files = || -> Result<Vec<PathBuf>, git2::Error> {
+        let commit = repo.find_commit(oid)?;
+        let mut opts = git2::DiffOptions::new();
+
+        for pathspec in pathspecs {
+            opts.pathspec(pathspec);
         }
 
-        // Add other emojis if present
-        if let Some(other_str) = other {
-            other_str.split(':').filter(|s| !s.is_empty()).for_each(|code| {
-                //
-                if let Some(g) = get_by_shortcode(code) {
-                    emojis.insert(g.as_str().to_string());
-                }
-            });
+        let old_tree = commit.parent(0).ok().map(|parent| parent.tree()).transpose()?;
+        let diff = repo.diff_tree_to_tree(old_tree.as_ref(), Some(&commit.tree()?), Some(&mut opts))?;
+        let workdir = repo.workdir().unwrap_or_else(|| repo.path());
+
+        Ok(diff
+            .deltas()
+            .filter_map(|delta| delta.new_file().path().map(|path| workdir.join(path)))
+            .collect())
+    };
+
+    files().unwrap_or_default()
+}
+
+/// Whether a commit changes any of the pathspecs compared to each of its parents.
+///
+/// Like `git log -- <path>`, merges that match one of their parents for the given paths are skipped.
+fn touches_paths(repo: &git2::Repository, commit: &git2::Commit<'_>, pathspecs: &[String]) -> bool {
+    //
+    if pathspecs.is_empty() {
+        return true;
+    }
+
+    let changed = |parent: Option<git2::Commit<'_>>| -> Result<bool, git2::Error> {
+        let mut opts = git2::DiffOptions::new();
+
+        for pathspec in pathspecs {
+            opts.pathspec(pathspec);
         }
 
-        emojis.into_iter().collect::<Vec<_>>().join(" ")
+        let old_tree = parent.map(|parent| parent.tree()).transpose()?;
+        let diff = repo.diff_tree_to_tree(old_tree.as_ref(), Some(&commit.tree()?), Some(&mut opts))?;
+
+        Ok(diff.deltas().len() > 0)
+    };
+
+    if commit.parent_count() == 0 {
+        return changed(None).unwrap_or_default();
     }
 
-    pub fn format(&self, now: &Zoned, printer: &SpanPrinter) -> Result<String> {
-        //
-        let text = &self.message;
-        let mut formatted = text.clone();
-
-        // Try to parse as a conventional commit
-        if let Ok(cc) = ConventionalCommit::parse(text) {
-            let type_str = cc.type_().to_string();
-            let scope = cc.scope().map(|s| s.as_str());
-            let breaking = cc.breaking();
-            let description = cc.description();
-
-            // Extract any existing emoji codes from the description
-            let other = if description.contains(':') {
-                Some(description)
-            } else {
-                None
-            };
+    commit.parents().all(|parent| changed(Some(parent)).unwrap_or_default())
+}
 
-            let emoji = Self::format_emoji(&type_str, scope, other, breaking);
+/// Compile a shell-style glob (`ui*`, `api-v?`) into an anchored regex.
+pub fn glob(pattern: &str) -> Result<Regex, regex::Error> {
+    //
+    let mut expr = String::from("^");
 
-            if !emoji.is_empty() {
-                let mut header = type_str;
+    for c in pattern.chars() {
+        match c {
+            '*' => expr.push_str(".*"),
+            '?' => expr.push('.'),
+            c => expr.push_str(&regex::escape(&c.to_string())),
+        }
+    }
 
-                if let Some(scope_str) = scope {
-                    header.push_str(&format!("({})", scope_str.bold()));
-                }
+    expr.push('$');
 
-                if breaking {
-                    header.push('!');
-                }
+    Regex::new(&expr)
+}
 
-                formatted = format!("{} {emoji} {description}", format!("{header}:").blue());
-            }
+/// The first line of a commit message with emoji and highlighting, given its parsed conventional header if any.
+#[must_use]
+pub fn highlight(cc: Option<&Conventional>, message: &str) -> String {
+    //
+    if let Some(cc) = cc {
+        return cc.highlighted();
+    }
+
+    match gitmoji(message) {
+        Some((emoji, rest)) => format!("{} {}", emoji::styled(emoji), strip_pull_request(rest)),
+        None => strip_pull_request(message.lines().next().unwrap_or_default()).to_string(),
+    }
+}
+
+/// Format a commit message read from outside a repository, as `highlight` does for commits.
+#[must_use]
+pub fn format_message(message: &str) -> String {
+    highlight(Conventional::parse(message).as_ref(), message.trim())
+}
+
+/// The leading gitmoji of a non-conventional subject and the text after it, with `--emoji-set gitmoji`.
+fn gitmoji(message: &str) -> Option<(&'static str, &str)> {
+    //
+    if emoji::emoji_set() != EmojiSet::Gitmoji {
+        return None;
+    }
+
+    emoji::gitmoji_prefix(message.trim().lines().next().unwrap_or_default())
+}
+
+/// Issue tracker keys and the URL template they link to, from the `[tracker]` setting.
+static TRACKER: OnceLock<(Regex, String)> = OnceLock::new();
+
+/// Link keys matching `pattern` to `url`, with `{key}` replaced by the key, for the rest of the run.
+pub fn set_tracker(pattern: Regex, url: String) {
+    let _ = TRACKER.set((pattern, url));
+}
+
+/// Issue tracker keys in `text` linked through the configured URL template.
+///
+/// Keys must start a word, though they may follow the escape codes that color a scope; `GH-` keys are left to
+/// `Commit::autolink`.
+fn link_tracker_keys(text: &str, markup: Markup) -> String {
+    //
+    let Some((pattern, url)) = TRACKER.get() else {
+        return text.to_string();
+    };
+
+    let mut linked = String::new();
+    let mut last = 0;
+
+    for key in pattern.find_iter(text) {
+        if key.as_str().starts_with("GH-") || !at_word_start(&text[..key.start()]) {
+            continue;
         }
 
-        // Emit a string in the form of: "(1 year, 4 months, 28 days, 18 hours ago)"
-        let span = (now - &self.timestamp).round(
-            SpanRound::new()
-                .largest(Unit::Year)
-                .smallest(Unit::Hour)
-                .relative(&self.timestamp),
-        )?;
+        linked.push_str(&text[last..key.start()]);
+        linked.push_str(&markup.link(&url.replace("{key}", key.as_str()), key.as_str()));
+        last = key.end();
+    }
+
+    linked.push_str(&text[last..]);
+    linked
+}
+
+/// Whether text following `before` starts a new word, looking through any trailing ANSI color codes.
+fn at_word_start(before: &str) -> bool {
+    //
+    let before = match before.rfind("\x1B[") {
+        Some(start)
+            if before.ends_with('m')
+                && before[start + 2..before.len() - 1]
+                    .bytes()
+                    .all(|b| b.is_ascii_digit() || b == b';') =>
+        {
+            &before[..start]
+        }
+        _ => before,
+    };
 
-        formatted.push_str(&format!(" ({} ago)", &printer.span_to_string(&span)));
+    !before
+        .chars()
+        .next_back()
+        .is_some_and(|c| c.is_alphanumeric() || c == '_')
+}
+
+/// Whether a squash merge's trailing "(#123)" is dropped from its subject, as selected by `--strip-pr`.
+static STRIP_PULL_REQUESTS: AtomicBool = AtomicBool::new(false);
+
+/// Drop squash merges' trailing pull request numbers from subjects for the rest of the run.
+pub fn set_strip_pull_requests(enabled: bool) {
+    STRIP_PULL_REQUESTS.store(enabled, Ordering::Relaxed);
+}
 
-        Ok(formatted.trim().lines().next().unwrap_or_default().to_string())
+/// A subject without its trailing "(#123)" when `--strip-pr` is set.
+fn strip_pull_request(subject: &str) -> &str {
+    //
+    if !STRIP_PULL_REQUESTS.load(Ordering::Relaxed) {
+        return subject;
     }
+
+    subject
+        .trim_end()
+        .strip_suffix(')')
+        .and_then(|rest| rest.rsplit_once(" (#"))
+        .filter(|(_, number)| !number.is_empty() && number.bytes().all(|b| b.is_ascii_digit()))
+        .map_or(subject, |(subject, _)| subject)
+}
+
+/// Nonstandard commit types mapped onto canonical ones, such as `hotfix` onto `fix`.
+static TYPE_ALIASES: OnceLock<HashMap<String, String>> = OnceLock::new();
+
+/// Install configured type aliases for the rest of the run.
+pub fn set_type_aliases(aliases: HashMap<String, String>) {
+    let _ = TYPE_ALIASES.set(aliases);
+}
+
+/// Commit types used besides the usual ones, from the `types` setting or a devmoji config.
+static CUSTOM_TYPES: OnceLock<Vec<String>> = OnceLock::new();
+
+/// Install configured commit types for the rest of the run.
+pub fn set_custom_types(types: Vec<String>) {
+    let _ = CUSTOM_TYPES.set(types);
 }
 
+/// Whether a commit type is one of the configured ones.
 #[must_use]
-fn commit_emoji(key: &str) -> Option<&'static str> {
-    match key {
-        "add" => Some("➕"),                                     // heavy_plus_sign
-        "android" => Some("🤖"),                                 // robot
-        "breaking" => Some("💥"),                                // boom
-        "build" | "deps" | "dep" | "dependencies" => Some("📦"), // package
-        "chore" | "maintenance" => Some("🔧"),                   // wrench
-        "ci" | "cd" => Some("👷"),                               // construction_worker
-        "config" => Some("⚙️"),                                  // gear
-        "doc" | "docs" | "documentation" => Some("📚"),          // books
-        "docker" => Some("🐳"),                                  // whale
-        "feat" | "feature" => Some("✨"),                        // sparkles
-        "fix" => Some("🐛"),                                     // bug
-        "i18n" | "l10n" => Some("🌐"),                           // globe_with_meridians
-        "kubernetes" | "k8s" => Some("☸️"),                      // wheel_of_dharma
-        "lint" | "linter" => Some("🚨"),                         // rotating_light
-        "linux" => Some("🐧"),                                   // penguin
-        "macos" | "ios" => Some("🍎"),                           // apple
-        "merge" => Some("🔀"),                                   // twisted_rightwards_arrows
-        "perf" | "performance" => Some("⚡️"),                    // zap
-        "ref" | "refactor" => Some("♻️"),                        // recycle
-        "release" => Some("🚀"),                                 // rocket
-        "remove" => Some("➖"),                                  // heavy_minus_sign
-        "revert" => Some("⏪"),                                  // rewind
-        "security" => Some("🔒"),                                // lock
-        "style" => Some("🎨"),                                   // art
-        "test" | "tests" => Some("✅"),                          // white_check_mark
-        "typo" | "typos" => Some("✏️"),                          // pencil2
-        "ui" | "ux" => Some("💄"),                               // lipstick
-        "windows" => Some("🏁"),                                 // checkered_flag
-        "wip" => Some("🚧"),                                     // construction
-        _ => None,
+pub fn is_custom_type(kind: &str) -> bool {
+    CUSTOM_TYPES
+        .get()
+        .is_some_and(|types| types.iter().any(|custom| custom == kind))
+}
+
+/// Whether `hyperlink` emits escape sequences, in the manner of `colored::control`.
+static HYPERLINKS: AtomicBool = AtomicBool::new(true);
+
+/// Enable or disable OSC-8 hyperlinks for the rest of the run.
+pub fn set_hyperlinks(enabled: bool) {
+    HYPERLINKS.store(enabled, Ordering::Relaxed);
+}
+
+/// Whether to write OSC-8 hyperlinks, as chosen by `--hyperlinks`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum HyperlinkMode {
+    /// When writing to a terminal known to support them.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+/// Whether the terminal is known to render OSC-8 hyperlinks, rather than print their escape codes as garbage.
+///
+/// `FORCE_HYPERLINK=1` or `0` overrides the guess.
+#[must_use]
+pub fn supports_hyperlinks() -> bool {
+    //
+    let var = |name| std::env::var(name).unwrap_or_default();
+
+    match var("FORCE_HYPERLINK").as_str() {
+        "1" => return true,
+        "0" => return false,
+        _ => {}
+    }
+
+    // Multiplexers only pass the sequences through when configured to.
+    if !var("TMUX").is_empty() || var("TERM").starts_with("screen") {
+        return false;
+    }
+
+    if matches!(
+        var("TERM_PROGRAM").as_str(),
+        "iTerm.app" | "WezTerm" | "vscode" | "ghostty" | "Hyper" | "Tabby" | "rio"
+    ) {
+        return true;
+    }
+
+    if var("VTE_VERSION").parse::<u32>().is_ok_and(|version| version >= 5000) {
+        return true;
+    }
+
+    if ["WT_SESSION", "KONSOLE_VERSION", "KITTY_WINDOW_ID"]
+        .into_iter()
+        .any(|name| !var(name).is_empty())
+    {
+        return true;
     }
+
+    let term = var("TERM");
+
+    ["xterm-kitty", "alacritty", "foot", "xterm-ghostty", "wezterm"]
+        .into_iter()
+        .any(|known| term.starts_with(known))
 }
 
-/// Emit an OSC-8 hyperlink escape sequence.
+/// Emit an OSC-8 hyperlink escape sequence, or just the text when hyperlinks are disabled.
 pub fn hyperlink(url: &str, text: &str) -> String {
     //
+    if !HYPERLINKS.load(Ordering::Relaxed) {
+        return text.cyan().to_string();
+    }
+
     format!("\x1B]8;;{url}\x1B\\{text}\x1B]8;;\x1B\\").cyan().to_string()
 }
 
+/// Serialize a value through its `Display` implementation, e.g. a full commit hash.
+fn serialize_display<S: Serializer>(value: &impl std::fmt::Display, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(value)
+}
+
+/// Serialize a timestamp in RFC 3339 form, keeping the commit's UTC offset.
+fn serialize_timestamp<S: Serializer>(timestamp: &Zoned, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(&timestamp.strftime("%Y-%m-%dT%H:%M:%S%:z"))
+}
+
 /// Turn a `git2::Time` into a `jiff::Zoned` timestamp, taking into account the TZ offset.
-fn zoned_from_time(time: &git2::Time) -> Zoned {
+pub fn zoned_from_time(time: &git2::Time) -> Zoned {
     Timestamp::from_second(time.seconds())
         .unwrap()
         .to_zoned(TimeZone::fixed(
             Offset::from_seconds(time.offset_minutes() * 60).unwrap(),
         ))
 }
+
+#[cfg(test)]
+impl Commit {
+    /// A commit with `message` made at `timestamp` by Alice, for tests of what is done with commits once walked.
+    pub fn fixture(message: &str, timestamp: &str) -> Commit {
+        Commit {
+            oid: git2::Oid::zero(),
+            id: "0000000".to_string(),
+            message: message.to_string(),
+            conventional: Conventional::parse(message),
+            trailers: parse_trailers(message),
+            timestamp: timestamp.parse().unwrap(),
+            url: None,
+            branches: Vec::new(),
+            author_name: "Alice".to_string(),
+            author_email: "alice@example.com".to_string(),
+            parent_count: 1,
+            release: None,
+            repo: None,
+            signature: None,
+            stats: None,
+            files: Vec::new(),
+            pull: None,
+            checks: None,
+            login: None,
+            action: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A query selecting every commit, for tests to narrow down.
+    fn query() -> Query {
+        Query {
+            types: Vec::new(),
+            scopes: Vec::new(),
+            breaking_only: false,
+            bots: Vec::new(),
+            signatures: false,
+            date_source: DateSource::default(),
+            trailers: Vec::new(),
+            count: usize::MAX,
+            skip: 0,
+            range: None,
+            branch: None,
+            not: Vec::new(),
+            branch_point: None,
+            all: false,
+            since: None,
+            until: None,
+            author: None,
+            grep: None,
+            paths: Vec::new(),
+            package: None,
+            no_merges: false,
+            merges_only: false,
+            first_parent: false,
+            unreleased: false,
+            releases: false,
+            remote: None,
+            abbrev: None,
+            stats: false,
+            enrich: None,
+            checks: false,
+            logins: false,
+        }
+    }
+
+    /// A repository in a fresh temporary directory with an empty commit per message, a minute apart and oldest first.
+    fn repo(name: &str, messages: &[&str]) -> (PathBuf, git2::Repository) {
+        //
+        let path = std::env::temp_dir().join(format!("devmoji-log-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&path);
+        let repo = git2::Repository::init(&path).unwrap();
+
+        for (minute, message) in (0..).zip(messages) {
+            let parents: &[&str] = if minute == 0 { &[] } else { &["HEAD"] };
+
+            commit_onto(&repo, "HEAD", message, parents, minute);
+        }
+
+        (path, repo)
+    }
+
+    /// Make an empty commit on `reference` with the given parent revisions, `minute` minutes into the history.
+    fn commit_onto(repo: &git2::Repository, reference: &str, message: &str, parents: &[&str], minute: i64) {
+        //
+        let tree = repo.find_tree(repo.index().unwrap().write_tree().unwrap()).unwrap();
+        let signature = git2::Signature::new(
+            "Alice",
+            "alice@example.com",
+            &git2::Time::new(1_738_000_000 + minute * 60, 0),
+        )
+        .unwrap();
+        let parents: Vec<_> = parents
+            .iter()
+            .map(|parent| repo.revparse_single(parent).unwrap().peel_to_commit().unwrap())
+            .collect();
+
+        repo.commit(
+            Some(reference),
+            &signature,
+            &signature,
+            message,
+            &tree,
+            &parents.iter().collect::<Vec<_>>(),
+        )
+        .unwrap();
+    }
+
+    /// Write `file` and commit it onto HEAD, `minute` minutes into the history.
+    fn commit_file(repo: &git2::Repository, file: &str, message: &str, minute: i64) {
+        //
+        let path = repo.workdir().unwrap().join(file);
+
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, message).unwrap();
+
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new(file)).unwrap();
+        index.write().unwrap();
+
+        commit_onto(repo, "HEAD", message, &["HEAD"], minute);
+    }
+
+    fn subjects(commits: &[Commit]) -> Vec<&str> {
+        commits.iter().map(|commit| commit.message.as_str()).collect()
+    }
+
+    #[test]
+    fn since_and_until_are_inclusive() {
+        let query = Query {
+            since: Some("2025-01-01T00:00:00[UTC]".parse().unwrap()),
+            until: Some("2025-01-31T00:00:00[UTC]".parse().unwrap()),
+            ..query()
+        };
+        let matches = |timestamp: &str| query.matches(&Commit::fixture("fix: thing", timestamp));
+
+        assert!(matches("2025-01-01T00:00:00[UTC]"));
+        assert!(matches("2025-01-31T00:00:00[UTC]"));
+        assert!(!matches("2024-12-31T23:59:59[UTC]"));
+        assert!(!matches("2025-01-31T00:00:01[UTC]"));
+    }
+
+    #[test]
+    fn ranges_select_commits_like_git_log() {
+        let (path, repo) = repo("ranges", &["feat: one", "fix: two", "feat: three"]);
+        repo.tag_lightweight("v1.0.0", &repo.revparse_single("HEAD~1").unwrap(), false)
+            .unwrap();
+
+        let walk = |range: &str| {
+            Commit::query(
+                &path,
+                &Query {
+                    range: Some(range.to_string()),
+                    ..query()
+                },
+            )
+            .unwrap()
+        };
+
+        assert_eq!(subjects(&walk("v1.0.0..HEAD")), ["feat: three"]);
+        assert_eq!(subjects(&walk("v1.0.0..")), ["feat: three"]);
+        assert_eq!(subjects(&walk("HEAD~2..v1.0.0")), ["fix: two"]);
+        assert_eq!(subjects(&walk("v1.0.0")), ["fix: two", "feat: one"]);
+
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn author_matches_name_or_email() {
+        let bob = Commit {
+            author_name: "Bob".to_string(),
+            author_email: "bob@corp.example".to_string(),
+            ..Commit::fixture("fix: thing", "2025-01-31T12:00:00[UTC]")
+        };
+        let matches = |pattern: &str| {
+            Query {
+                author: Some(Regex::new(pattern).unwrap()),
+                ..query()
+            }
+            .matches(&bob)
+        };
+
+        assert!(matches("Bob"));
+        assert!(matches("@corp"));
+        assert!(matches("^Bob <bob@"));
+        assert!(!matches("Alice"));
+    }
+
+    #[test]
+    fn grep_searches_the_whole_message() {
+        let commit = Commit::fixture("fix: thing\n\nCloses the parser leak.", "2025-01-31T12:00:00[UTC]");
+        let matches = |pattern: &str| {
+            Query {
+                grep: Some(Regex::new(pattern).unwrap()),
+                ..query()
+            }
+            .matches(&commit)
+        };
+
+        assert!(matches("^fix"));
+        assert!(matches("parser leak"));
+        assert!(!matches("Parser"));
+    }
+
+    #[test]
+    fn merges_are_hidden_or_kept_alone() {
+        let fix = Commit::fixture("fix: thing", "2025-01-31T12:00:00[UTC]");
+        let merge = Commit {
+            parent_count: 2,
+            ..Commit::fixture("Merge branch 'topic'", "2025-01-31T12:00:00[UTC]")
+        };
+        let no_merges = Query {
+            no_merges: true,
+            ..query()
+        };
+        let merges_only = Query {
+            merges_only: true,
+            ..query()
+        };
+
+        assert!(query().matches(&merge));
+        assert!(no_merges.matches(&fix));
+        assert!(!no_merges.matches(&merge));
+        assert!(merges_only.matches(&merge));
+        assert!(!merges_only.matches(&fix));
+    }
+
+    #[test]
+    fn unreleased_stops_at_the_latest_version_tag() {
+        let (path, repo) = repo("unreleased", &["feat: one", "fix: two", "feat: three", "fix: four"]);
+        repo.tag_lightweight("v1.0.0", &repo.revparse_single("HEAD~3").unwrap(), false)
+            .unwrap();
+        repo.tag_lightweight("v1.1.0", &repo.revparse_single("HEAD~2").unwrap(), false)
+            .unwrap();
+
+        let unreleased = Query {
+            unreleased: true,
+            ..query()
+        };
+
+        assert_eq!(
+            subjects(&Commit::query(&path, &unreleased).unwrap()),
+            ["fix: four", "feat: three"]
+        );
+
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn skip_pages_through_the_filtered_commits() {
+        let (path, _repo) = repo(
+            "skip",
+            &["feat: one", "chore: two", "fix: three", "chore: four", "feat: five"],
+        );
+        let page = |skip| Query {
+            types: vec!["feat".to_string(), "fix".to_string()],
+            count: 2,
+            skip,
+            ..query()
+        };
+
+        assert_eq!(
+            subjects(&Commit::query(&path, &page(0)).unwrap()),
+            ["feat: five", "fix: three"]
+        );
+        assert_eq!(subjects(&Commit::query(&path, &page(2)).unwrap()), ["feat: one"]);
+        assert_eq!(subjects(&Commit::query(&path, &page(3)).unwrap()), Vec::<&str>::new());
+
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn type_matches_conventional_commits_only() {
+        let query = Query {
+            types: vec!["feat".to_string(), "Fix".to_string()],
+            ..query()
+        };
+        let matches = |message: &str| query.matches(&Commit::fixture(message, "2025-01-31T12:00:00[UTC]"));
+
+        assert!(matches("feat(ui): add a button"));
+        assert!(matches("fix!: drop the old flag"));
+        assert!(!matches("chore: bump deps"));
+        assert!(!matches("feat without a colon"));
+    }
+
+    #[test]
+    fn scope_matches_globs_and_never_unscoped_commits() {
+        let query = Query {
+            scopes: vec![glob("ui*").unwrap(), glob("api-v?").unwrap()],
+            ..query()
+        };
+        let matches = |message: &str| query.matches(&Commit::fixture(message, "2025-01-31T12:00:00[UTC]"));
+
+        assert!(matches("feat(ui): add a button"));
+        assert!(matches("feat(ui-kit): add a button"));
+        assert!(matches("fix(api-v2): handle nulls"));
+        assert!(!matches("fix(api-v10): handle nulls"));
+        assert!(!matches("fix(build-ui): cache"));
+        assert!(!matches("fix: handle nulls"));
+    }
+
+    #[test]
+    fn breaking_only_keeps_bangs_and_footers() {
+        let query = Query {
+            breaking_only: true,
+            ..query()
+        };
+        let matches = |message: &str| query.matches(&Commit::fixture(message, "2025-01-31T12:00:00[UTC]"));
+
+        assert!(matches("feat(api)!: drop v1"));
+        assert!(matches("feat: new config\n\nBREAKING CHANGE: the old keys are gone"));
+        assert!(!matches("feat: new config"));
+        assert!(!matches("breaking: not a change"));
+    }
+
+    #[test]
+    fn no_bots_hides_builtin_and_configured_bots() {
+        let by = |name: &str, email: &str| Commit {
+            author_name: name.to_string(),
+            author_email: email.to_string(),
+            ..Commit::fixture("chore: bump deps", "2025-01-31T12:00:00[UTC]")
+        };
+        let config = crate::config::Config {
+            bots: vec!["^release-robot".to_string()],
+            ..crate::config::Config::default()
+        };
+        let query = Query {
+            bots: config.bot_patterns().unwrap(),
+            ..query()
+        };
+
+        assert!(!query.matches(&by(
+            "dependabot[bot]",
+            "49699333+dependabot[bot]@users.noreply.github.com"
+        )));
+        assert!(!query.matches(&by("renovate", "bot@renovateapp.com")));
+        assert!(!query.matches(&by("release-robot", "robot@example.com")));
+        assert!(query.matches(&by("Alice", "alice@example.com")));
+    }
+
+    #[test]
+    fn trailer_matches_keys_and_value_substrings() {
+        let commit = Commit::fixture(
+            "fix: thing\n\nReviewed-by: Alice Smith <alice@example.com>\nTested-by: Bob",
+            "2025-01-31T12:00:00[UTC]",
+        );
+        let matches = |filter: &str| {
+            Query {
+                trailers: vec![TrailerFilter::parse(filter).unwrap()],
+                ..query()
+            }
+            .matches(&commit)
+        };
+
+        assert!(matches("reviewed-by"));
+        assert!(matches("Reviewed-by=alice"));
+        assert!(matches("Tested-by = bob"));
+        assert!(!matches("Reviewed-by=bob"));
+        assert!(!matches("Signed-off-by"));
+        assert!(TrailerFilter::parse("=alice").is_err());
+    }
+
+    #[test]
+    fn branch_walks_another_branch_than_head() {
+        let (path, repo) = repo("branch", &["feat: one", "fix: two"]);
+        commit_onto(&repo, "refs/heads/topic", "feat: topic", &["HEAD~1"], 5);
+
+        let topic = Query {
+            branch: Some("topic".to_string()),
+            ..query()
+        };
+
+        assert_eq!(
+            subjects(&Commit::query(&path, &topic).unwrap()),
+            ["feat: topic", "feat: one"]
+        );
+        assert_eq!(
+            subjects(&Commit::query(&path, &query()).unwrap()),
+            ["fix: two", "feat: one"]
+        );
+
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn all_walks_every_branch_and_decorates_commits() {
+        let (path, repo) = repo("all", &["feat: one", "fix: two"]);
+        commit_onto(&repo, "refs/heads/topic", "feat: topic", &["HEAD~1"], 5);
+
+        let main = repo.head().unwrap().shorthand().unwrap().to_string();
+        let all = Query { all: true, ..query() };
+        let commits = Commit::query(&path, &all).unwrap();
+        let branches: Vec<_> = commits.iter().map(|commit| commit.branches.join(", ")).collect();
+
+        assert_eq!(subjects(&commits), ["feat: topic", "fix: two", "feat: one"]);
+        assert_eq!(branches, ["topic".to_string(), main.clone(), format!("{main}, topic")]);
+
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn first_parent_leaves_out_merged_branches() {
+        let (path, repo) = repo("first-parent", &["feat: one", "fix: two"]);
+        commit_onto(&repo, "refs/heads/topic", "feat: topic", &["HEAD~1"], 5);
+        commit_onto(&repo, "HEAD", "Merge branch 'topic'", &["HEAD", "topic"], 6);
+
+        let first_parent = Query {
+            first_parent: true,
+            ..query()
+        };
+
+        assert_eq!(
+            subjects(&Commit::query(&path, &query()).unwrap()),
+            ["Merge branch 'topic'", "feat: topic", "fix: two", "feat: one"]
+        );
+        assert_eq!(
+            subjects(&Commit::query(&path, &first_parent).unwrap()),
+            ["Merge branch 'topic'", "fix: two", "feat: one"]
+        );
+
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn paths_select_commits_touching_them() {
+        let (path, repo) = repo("paths", &["chore: init"]);
+
+        commit_file(&repo, "src/a.rs", "feat: a", 1);
+        commit_file(&repo, "docs/b.md", "docs: b", 2);
+        commit_file(&repo, "src/c.rs", "fix: c", 3);
+
+        let paths = |paths: &[&str]| {
+            let commits = Commit::query(
+                &path,
+                &Query {
+                    paths: paths.iter().map(ToString::to_string).collect(),
+                    ..query()
+                },
+            )
+            .unwrap();
+
+            subjects(&commits).iter().map(ToString::to_string).collect::<Vec<_>>()
+        };
+
+        assert_eq!(paths(&["src"]), ["fix: c", "feat: a"]);
+        assert_eq!(paths(&["docs/b.md", "src/a.rs"]), ["docs: b", "feat: a"]);
+
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn package_selects_commits_in_its_subtree_or_scoped_to_it() {
+        let (path, repo) = repo("package", &["chore: init"]);
+
+        commit_file(&repo, "packages/parser/lib.rs", "feat: parse more", 1);
+        commit_file(&repo, "packages/cli/main.rs", "fix: print less", 2);
+        commit_file(&repo, "README.md", "docs(parser): explain parsing", 3);
+        commit_file(&repo, "CHANGELOG.md", "docs(packages/cli): note the fix", 4);
+
+        let package = |package: &str| {
+            let commits = Commit::query(
+                &path,
+                &Query {
+                    package: Some(package.to_string()),
+                    ..query()
+                },
+            )
+            .unwrap();
+
+            subjects(&commits).iter().map(ToString::to_string).collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            package("packages/parser"),
+            ["docs(parser): explain parsing", "feat: parse more"]
+        );
+        assert_eq!(
+            package("packages/cli/"),
+            ["docs(packages/cli): note the fix", "fix: print less"]
+        );
+
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn autolink_links_issues_and_the_squashed_pull_request() {
+        let commit = Commit {
+            url: Some("https://github.com/acme/widget".to_string()),
+            ..Commit::fixture("fix: close the socket (#12)", "2025-01-31T12:00:00[UTC]")
+        };
+
+        assert_eq!(
+            commit.autolink("closes #7 and GH-8, not a#9 (#12)", Markup::Markdown),
+            "closes [#7](https://github.com/acme/widget/issues/7) and [GH-8](https://github.com/acme/widget/issues/8), \
+             not a#9 ([#12](https://github.com/acme/widget/pull/12))"
+        );
+        assert_eq!(
+            Commit::fixture("fix: closes #7", "2025-01-31T12:00:00[UTC]").autolink("closes #7", Markup::Markdown),
+            "closes #7"
+        );
+    }
+}
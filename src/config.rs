@@ -0,0 +1,285 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::emoji::EmojiSet;
+
+/// Author patterns of well known bots, matched against "Name <email>".
+const BOTS: &[&str] = &[
+    r"\[bot\]",
+    r"^dependabot",
+    r"^renovate",
+    r"^github-actions",
+    r"^pre-commit-ci",
+    r"^snyk-bot",
+    r"^mergify",
+];
+
+/// Settings from `~/.config/devmoji-log/config.toml`, overridden by a per-repository `.devmoji-log.toml`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Extra author patterns treated as bots by `--no-bots`.
+    pub bots: Vec<String>,
+
+    /// Whether the builtin bot patterns apply, defaulting to true.
+    pub builtin_bots: Option<bool>,
+
+    /// Builtin emoji table, unless given by `--emoji-set`.
+    pub emoji_set: Option<EmojiSet>,
+
+    /// Emoji or `:shortcode:` for conventional types missing from the tables, such as `"🔹"`.
+    pub fallback_emoji: Option<String>,
+
+    /// Emoji or `:shortcodes:` for types, scopes and `type-scope` pairs, overriding the builtin table.
+    pub emoji: HashMap<String, String>,
+
+    /// Emoji or `:shortcodes:` for scopes, such as `scope.parser = "🌳"`, taking precedence over `emoji`.
+    pub scope: HashMap<String, String>,
+
+    /// Nonstandard commit types mapped onto canonical ones, such as `hotfix = "fix"`.
+    pub aliases: HashMap<String, String>,
+
+    /// Commit types used besides the usual ones, such as `wip`, which `lint` accepts.
+    pub types: Vec<String>,
+
+    /// Commit link template for self-hosted or unusual forges, such as `https://git.corp/x/{repo}/-/commit/{sha}`.
+    pub commit_url: Option<String>,
+
+    /// Issue tracker whose keys, such as `PROJ-1234`, are linked in subjects and scopes.
+    pub tracker: Option<Tracker>,
+
+    /// A self-hosted GitLab instance, for links and `--enrich gitlab`.
+    pub gitlab: Option<GitLab>,
+
+    /// A GitHub Enterprise instance, for `--enrich github` and `--checks`.
+    pub github: Option<GitHub>,
+}
+
+/// An issue tracker such as JIRA, for linking its keys.
+#[derive(Debug, Deserialize)]
+pub struct Tracker {
+    /// Link template, such as `https://jira.example.com/browse/{key}`.
+    pub url: String,
+
+    /// Pattern of the keys to link, defaulting to JIRA's `PROJ-1234` form.
+    pub pattern: Option<String>,
+}
+
+/// A self-hosted GitLab instance, whose host needn't mention GitLab.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct GitLab {
+    /// Base URL of the instance, such as `https://git.corp`.
+    pub url: Option<String>,
+
+    /// Personal access token for its API, used in place of `GITLAB_TOKEN`.
+    pub token: Option<String>,
+}
+
+/// A GitHub Enterprise instance, whose API is only asked once configured so tokens stay with their hosts.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct GitHub {
+    /// Base URL of the instance, such as `https://github.corp`.
+    pub url: Option<String>,
+}
+
+/// The parts of a devmoji `devmoji.config.js` that map onto this tool's settings.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct DevmojiConfig {
+    devmoji: Vec<DevmojiCode>,
+    types: Vec<String>,
+}
+
+/// A custom devmoji code, such as `{ code: "chore-release", emoji: "rocket" }`, or naming a gitmoji instead.
+#[derive(Debug, Deserialize)]
+struct DevmojiCode {
+    code: String,
+    emoji: Option<String>,
+    gitmoji: Option<String>,
+}
+
+impl Config {
+    /// Load the global config file and the ones at the root of the repository containing `path`.
+    ///
+    /// A devmoji config is read between the two, so `.devmoji-log.toml` can still override its mappings. Forge
+    /// instances are only read from the global file: a cloned repository mustn't choose where API tokens are sent.
+    pub fn load(path: &Path) -> Result<Config> {
+        //
+        let mut config = Config::default();
+        let global = global_file();
+
+        for file in [global.clone(), devmoji_file(path), repo_file(path)]
+            .into_iter()
+            .flatten()
+        {
+            if file.is_file() {
+                let text = std::fs::read_to_string(&file).with_context(|| format!("Reading {}", file.display()))?;
+
+                let mut found: Config = if file.extension().is_some_and(|extension| extension == "toml") {
+                    toml::from_str(&text).with_context(|| format!("Parsing {}", file.display()))?
+                } else {
+                    // devmoji configs are JavaScript, which only sometimes reads as JSON, and belong to another tool.
+                    match Self::from_devmoji(&text) {
+                        Ok(found) => found,
+                        Err(e) => {
+                            eprintln!("devmoji-log: skipping {}: {e}", file.display());
+                            continue;
+                        }
+                    }
+                };
+
+                if global.as_ref() != Some(&file) && (found.github.is_some() || found.gitlab.is_some()) {
+                    eprintln!(
+                        "devmoji-log: ignoring [github] and [gitlab] in {}; set them in the global config",
+                        file.display()
+                    );
+
+                    found.github = None;
+                    found.gitlab = None;
+                }
+
+                config.merge(found);
+            }
+        }
+
+        Ok(config)
+    }
+
+    /// Read the custom codes and types of a devmoji config, given as JSON or as a `module.exports = {...}` script.
+    ///
+    /// Scripts may use the object literal conveniences of JavaScript: comments, bare keys, single quotes and
+    /// trailing commas, but nothing that needs evaluating.
+    fn from_devmoji(text: &str) -> Result<Config> {
+        //
+        let literal = text
+            .trim()
+            .trim_start_matches("module.exports")
+            .trim_start()
+            .trim_start_matches('=')
+            .trim_end_matches(';');
+
+        let json = Regex::new(r"(?m)^\s*//.*$")?.replace_all(literal, "");
+        let json = Regex::new(r"([{,]\s*)([A-Za-z_$][\w$]*)\s*:")?.replace_all(&json, r#"$1"$2":"#);
+        let json = Regex::new(r"'([^'\\]*)'")?.replace_all(&json, r#""$1""#);
+        let json = Regex::new(r",(\s*[}\]])")?.replace_all(&json, "$1");
+
+        let devmoji: DevmojiConfig = serde_json::from_str(&json)?;
+
+        Ok(Config {
+            emoji: devmoji
+                .devmoji
+                .into_iter()
+                .filter_map(|code| Some((code.code, code.emoji.or(code.gitmoji)?)))
+                .collect(),
+            types: devmoji.types,
+            ..Config::default()
+        })
+    }
+
+    /// Layer a more specific config on top of this one.
+    fn merge(&mut self, other: Config) {
+        self.bots.extend(other.bots);
+        self.builtin_bots = other.builtin_bots.or(self.builtin_bots);
+        self.emoji_set = other.emoji_set.or(self.emoji_set);
+        self.fallback_emoji = other.fallback_emoji.or(self.fallback_emoji.take());
+        self.emoji.extend(other.emoji);
+        self.scope.extend(other.scope);
+        self.aliases.extend(other.aliases);
+        self.types.extend(other.types);
+        self.tracker = other.tracker.or(self.tracker.take());
+        self.commit_url = other.commit_url.or(self.commit_url.take());
+        self.gitlab = other.gitlab.or(self.gitlab.take());
+        self.github = other.github.or(self.github.take());
+    }
+
+    /// The configured emoji table, with shortcodes resolved to the emoji they name.
+    pub fn emoji_table(&self) -> HashMap<String, String> {
+        resolve_shortcodes(&self.emoji)
+    }
+
+    /// The configured fallback emoji, with a shortcode resolved to the emoji it names.
+    pub fn fallback_emoji(&self) -> Option<String> {
+        self.fallback_emoji.as_deref().map(resolve_shortcode)
+    }
+
+    /// The configured scope emoji table, with shortcodes resolved to the emoji they name.
+    pub fn scope_table(&self) -> HashMap<String, String> {
+        resolve_shortcodes(&self.scope)
+    }
+
+    /// The compiled key pattern and link template of the configured issue tracker.
+    pub fn tracker(&self) -> Result<Option<(Regex, String)>> {
+        //
+        let Some(tracker) = &self.tracker else {
+            return Ok(None);
+        };
+
+        let pattern = tracker.pattern.as_deref().unwrap_or(r"[A-Z][A-Z0-9]+-\d+\b");
+        let regex = Regex::new(pattern).with_context(|| format!("Invalid tracker pattern: {pattern}"))?;
+
+        Ok(Some((regex, tracker.url.clone())))
+    }
+
+    /// Compiled author patterns for `--no-bots`.
+    pub fn bot_patterns(&self) -> Result<Vec<Regex>> {
+        //
+        let builtin = if self.builtin_bots.unwrap_or(true) { BOTS } else { &[] };
+
+        builtin
+            .iter()
+            .copied()
+            .chain(self.bots.iter().map(String::as_str))
+            .map(|pattern| Regex::new(pattern).with_context(|| format!("Invalid bot pattern: {pattern}")))
+            .collect()
+    }
+}
+
+/// Replace `:shortcode:` values with the emoji they name, leaving anything else as written.
+fn resolve_shortcodes(table: &HashMap<String, String>) -> HashMap<String, String> {
+    //
+    table
+        .iter()
+        .map(|(key, value)| (key.clone(), resolve_shortcode(value)))
+        .collect()
+}
+
+/// The emoji a `:shortcode:` names, or the value as written.
+fn resolve_shortcode(value: &str) -> String {
+    emojis::get_by_shortcode(value.trim_matches(':'))
+        .map_or_else(|| value.to_string(), |emoji| emoji.as_str().to_string())
+}
+
+/// `$XDG_CONFIG_HOME/devmoji-log/config.toml`, defaulting to `~/.config`.
+fn global_file() -> Option<PathBuf> {
+    //
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::home_dir().map(|home| home.join(".config")))?;
+
+    Some(base.join("devmoji-log").join("config.toml"))
+}
+
+/// `devmoji.config.json` or `devmoji.config.js` at the root of the repository containing `path`, if either exists.
+fn devmoji_file(path: &Path) -> Option<PathBuf> {
+    //
+    let repo = git2::Repository::discover(path).ok()?;
+    let workdir = repo.workdir()?;
+
+    ["devmoji.config.json", "devmoji.config.js"]
+        .into_iter()
+        .map(|name| workdir.join(name))
+        .find(|file| file.is_file())
+}
+
+/// `.devmoji-log.toml` at the root of the repository containing `path`.
+fn repo_file(path: &Path) -> Option<PathBuf> {
+    let repo = git2::Repository::discover(path).ok()?;
+
+    Some(repo.workdir()?.join(".devmoji-log.toml"))
+}
@@ -0,0 +1,156 @@
+use anyhow::{Result, bail};
+use jiff::civil::{Date, DateTime, Weekday};
+use jiff::{Span, Timestamp, Zoned};
+
+/// Parse a user supplied date such as `yesterday`, `2 weeks ago`, `2025-01-31` or an RFC 3339 timestamp.
+pub fn parse(input: &str) -> Result<Zoned> {
+    parse_relative_to(input, &Zoned::now())
+}
+
+/// Parse a user supplied date, resolving relative expressions against `now`.
+pub fn parse_relative_to(input: &str, now: &Zoned) -> Result<Zoned> {
+    //
+    let input = input.trim();
+
+    match input.to_lowercase().as_str() {
+        "now" => return Ok(now.clone()),
+        "today" => return Ok(now.start_of_day()?),
+        "yesterday" => return Ok(now.yesterday()?.start_of_day()?),
+        _ => {}
+    }
+
+    if let Ok(zoned) = input.parse::<Zoned>() {
+        return Ok(zoned);
+    }
+
+    if let Ok(timestamp) = input.parse::<Timestamp>() {
+        return Ok(timestamp.to_zoned(now.time_zone().clone()));
+    }
+
+    if let Ok(datetime) = input.parse::<DateTime>() {
+        return Ok(datetime.to_zoned(now.time_zone().clone())?);
+    }
+
+    if let Ok(date) = input.parse::<Date>() {
+        return Ok(date.to_zoned(now.time_zone().clone())?);
+    }
+
+    // Relative spans always point into the past: "2 weeks ago" and "2 weeks" mean the same thing.
+    if let Ok(span) = input.parse::<Span>() {
+        return Ok(now.checked_sub(span.abs())?);
+    }
+
+    bail!("Unable to parse date: {input}")
+}
+
+/// Parse a user supplied end date, where a day without a time, such as `yesterday` or `2025-01-31`, includes all of it.
+pub fn parse_until(input: &str) -> Result<Zoned> {
+    parse_until_relative_to(input, &Zoned::now())
+}
+
+/// Parse a user supplied end date, resolving relative expressions against `now`.
+pub fn parse_until_relative_to(input: &str, now: &Zoned) -> Result<Zoned> {
+    //
+    let input = input.trim();
+    let zoned = parse_relative_to(input, now)?;
+
+    let whole_day = matches!(input.to_lowercase().as_str(), "today" | "yesterday")
+        || (input.parse::<Date>().is_ok() && !input.contains(['T', 't', ' ']));
+
+    if whole_day {
+        return Ok(zoned.end_of_day()?);
+    }
+
+    Ok(zoned)
+}
+
+/// The start of the last weekday before `now`, so that on a Monday it is Friday.
+pub fn previous_working_day(now: &Zoned) -> Result<Zoned> {
+    //
+    let mut day = now.yesterday()?;
+
+    while matches!(day.weekday(), Weekday::Saturday | Weekday::Sunday) {
+        day = day.yesterday()?;
+    }
+
+    Ok(day.start_of_day()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn now() -> Zoned {
+        "2025-02-05T15:30:00[UTC]".parse().unwrap()
+    }
+
+    #[test]
+    fn parse_named_days() {
+        assert_eq!(parse_relative_to("now", &now()).unwrap(), now());
+        assert_eq!(
+            parse_relative_to("Today", &now()).unwrap().to_string(),
+            "2025-02-05T00:00:00+00:00[UTC]"
+        );
+        assert_eq!(
+            parse_relative_to("yesterday", &now()).unwrap().to_string(),
+            "2025-02-04T00:00:00+00:00[UTC]"
+        );
+    }
+
+    #[test]
+    fn parse_dates_and_timestamps() {
+        assert_eq!(
+            parse_relative_to("2025-01-31", &now()).unwrap().to_string(),
+            "2025-01-31T00:00:00+00:00[UTC]"
+        );
+        assert_eq!(
+            parse_relative_to("2025-01-31T12:00:00Z", &now()).unwrap().to_string(),
+            "2025-01-31T12:00:00+00:00[UTC]"
+        );
+    }
+
+    #[test]
+    fn parse_spans_into_the_past() {
+        let expected = "2025-01-22T15:30:00+00:00[UTC]";
+
+        assert_eq!(parse_relative_to("2 weeks ago", &now()).unwrap().to_string(), expected);
+        assert_eq!(parse_relative_to("2 weeks", &now()).unwrap().to_string(), expected);
+    }
+
+    #[test]
+    fn parse_rejects_nonsense() {
+        assert!(parse_relative_to("whenever", &now()).is_err());
+    }
+
+    #[test]
+    fn parse_until_includes_whole_days() {
+        assert_eq!(
+            parse_until_relative_to("yesterday", &now()).unwrap().to_string(),
+            "2025-02-04T23:59:59.999999999+00:00[UTC]"
+        );
+        assert_eq!(
+            parse_until_relative_to("2025-01-31", &now()).unwrap().to_string(),
+            "2025-01-31T23:59:59.999999999+00:00[UTC]"
+        );
+        assert_eq!(
+            parse_until_relative_to("2025-01-31T12:00:00Z", &now())
+                .unwrap()
+                .to_string(),
+            "2025-01-31T12:00:00+00:00[UTC]"
+        );
+    }
+
+    #[test]
+    fn previous_working_day_skips_weekends() {
+        let monday: Zoned = "2025-02-03T09:00:00[UTC]".parse().unwrap();
+
+        assert_eq!(
+            previous_working_day(&monday).unwrap().date(),
+            jiff::civil::date(2025, 1, 31)
+        );
+        assert_eq!(
+            previous_working_day(&now()).unwrap().date(),
+            jiff::civil::date(2025, 2, 4)
+        );
+    }
+}
@@ -0,0 +1,415 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use serde::Deserialize;
+
+/// The family of emoji conventions commits are rendered with.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum EmojiSet {
+    /// devmoji's table, keyed by conventional types and scopes.
+    #[default]
+    #[value(alias = "default")]
+    #[serde(alias = "default")]
+    Devmoji,
+    /// gitmoji's table, including subjects that start with a gitmoji such as ":sparkles: Add login".
+    Gitmoji,
+    /// Only features, fixes and breaking changes get an emoji.
+    Minimal,
+    /// Sober symbols for the main types, without bugs, explosions or lipstick.
+    CorporateSafe,
+}
+
+/// How emoji are written out.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EmojiStyle {
+    /// As Unicode emoji.
+    #[default]
+    Unicode,
+    /// As `:shortcode:`s, for GitHub and Slack which render them natively.
+    Shortcodes,
+    /// Not at all: conventional commits get colored `[type!]` text badges instead.
+    Badges,
+}
+
+/// Emoji in insertion order without duplicates, treating `⚡️` and `⚡` as the same emoji.
+#[derive(Debug, Default)]
+pub struct EmojiList(Vec<&'static str>);
+
+impl EmojiList {
+    /// Append an emoji unless it is already in the list.
+    pub fn insert(&mut self, emoji: &'static str) {
+        //
+        let bare = |e: &str| e.trim_end_matches('\u{fe0f}').to_string();
+
+        if !self.0.iter().any(|existing| bare(existing) == bare(emoji)) {
+            self.0.push(emoji);
+        }
+    }
+
+    #[must_use]
+    pub fn join(&self, separator: &str) -> String {
+        self.0.join(separator)
+    }
+}
+
+/// Emoji configured in `[emoji]` tables, consulted before the builtin table.
+static CUSTOM_EMOJI: OnceLock<HashMap<String, String>> = OnceLock::new();
+
+/// Emoji configured per scope, consulted before `CUSTOM_EMOJI` for scopes.
+static SCOPE_EMOJI: OnceLock<HashMap<String, String>> = OnceLock::new();
+
+/// Emoji for conventional types missing from every table, from the `fallback_emoji` setting.
+static FALLBACK_EMOJI: OnceLock<String> = OnceLock::new();
+
+/// The builtin table selected by `--emoji-set` or the `emoji_set` setting.
+static EMOJI_SET: OnceLock<EmojiSet> = OnceLock::new();
+
+/// How emoji are written out, as selected by `--shortcodes` or `--no-emoji`.
+static EMOJI_STYLE: OnceLock<EmojiStyle> = OnceLock::new();
+
+/// Whether emoji written into descriptions are dropped rather than rendered, as selected by `--strip-emoji`.
+static STRIP_EMOJI: AtomicBool = AtomicBool::new(false);
+
+/// Install configured emoji for types, scopes and `type-scope` pairs, overriding the builtin table.
+pub fn set_custom_emoji(emoji: HashMap<String, String>) {
+    let _ = CUSTOM_EMOJI.set(emoji);
+}
+
+/// Install configured emoji for scopes, overriding both `[emoji]` and the builtin table.
+pub fn set_scope_emoji(emoji: HashMap<String, String>) {
+    let _ = SCOPE_EMOJI.set(emoji);
+}
+
+/// Drop emoji written into descriptions for the rest of the run, keeping only those derived from the type.
+pub fn set_strip_emoji(enabled: bool) {
+    STRIP_EMOJI.store(enabled, Ordering::Relaxed);
+}
+
+/// Install the emoji shown for unknown conventional types, which otherwise get none.
+pub fn set_fallback_emoji(emoji: String) {
+    let _ = FALLBACK_EMOJI.set(emoji);
+}
+
+/// The emoji for unknown conventional types, if one is configured.
+#[must_use]
+pub fn fallback_emoji() -> Option<&'static str> {
+    FALLBACK_EMOJI.get().map(String::as_str)
+}
+
+/// Select the builtin table for the rest of the run.
+pub fn set_emoji_set(set: EmojiSet) {
+    let _ = EMOJI_SET.set(set);
+}
+
+/// Select how emoji are written out for the rest of the run.
+pub fn set_emoji_style(style: EmojiStyle) {
+    let _ = EMOJI_STYLE.set(style);
+}
+
+/// How emoji are written out.
+#[must_use]
+pub fn emoji_style() -> EmojiStyle {
+    EMOJI_STYLE.get().copied().unwrap_or_default()
+}
+
+/// Space separated emoji written out in the selected style.
+#[must_use]
+pub fn styled(emoji: &str) -> String {
+    match emoji_style() {
+        EmojiStyle::Unicode => emoji.to_string(),
+        EmojiStyle::Shortcodes => shortcodes(emoji),
+        EmojiStyle::Badges => String::new(),
+    }
+}
+
+/// Space separated emoji as `:shortcode:`s, leaving any without one as they are.
+#[must_use]
+pub fn shortcodes(emoji: &str) -> String {
+    emoji
+        .split(' ')
+        .filter(|e| !e.is_empty())
+        .map(|e| {
+            match emojis::get(e)
+                .or_else(|| emojis::get(e.trim_end_matches('\u{fe0f}')))
+                .and_then(emojis::Emoji::shortcode)
+            {
+                Some(code) => format!(":{code}:"),
+                None => e.to_string(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The builtin table in use.
+#[must_use]
+pub fn emoji_set() -> EmojiSet {
+    EMOJI_SET.get().copied().unwrap_or_default()
+}
+
+/// Emoji for a type, scope or `type-scope` key, preferring the configured table over the builtin one.
+#[must_use]
+pub fn commit_emoji(key: &str) -> Option<&'static str> {
+    //
+    if let Some(emoji) = CUSTOM_EMOJI.get().and_then(|custom| custom.get(key)) {
+        return Some(emoji);
+    }
+
+    match emoji_set() {
+        EmojiSet::Devmoji => devmoji(key),
+        EmojiSet::Gitmoji => gitmoji(key).or_else(|| devmoji(key)),
+        EmojiSet::Minimal => minimal(key),
+        EmojiSet::CorporateSafe => corporate_safe(key),
+    }
+}
+
+/// Whether an emoji is configured for a key, such as a team's own commit type.
+#[must_use]
+pub fn is_configured(key: &str) -> bool {
+    CUSTOM_EMOJI.get().is_some_and(|custom| custom.contains_key(key))
+}
+
+/// Emoji for a scope, preferring the configured scope table.
+#[must_use]
+pub fn scope_emoji(scope: &str) -> Option<&'static str> {
+    //
+    if let Some(emoji) = SCOPE_EMOJI.get().and_then(|custom| custom.get(scope)) {
+        return Some(emoji);
+    }
+
+    commit_emoji(scope)
+}
+
+/// Move the emoji and `:shortcodes:` leading a description out of it, returning them and the rest of it.
+///
+/// Commits written with devmoji or gitmoji hooks already carry their emoji, which would otherwise render twice.
+/// Emoji further along are the author's words and stay where they are. With `--strip-emoji` nothing is returned,
+/// and emoji inside words are removed as well.
+#[must_use]
+pub fn extract(description: &str) -> (Vec<&'static str>, String) {
+    //
+    let (found, rest) = leading(description.trim());
+
+    if STRIP_EMOJI.load(Ordering::Relaxed) {
+        let text = rest
+            .split_whitespace()
+            .map(strip)
+            .filter(|word| !word.is_empty())
+            .collect::<Vec<_>>();
+
+        return (Vec::new(), text.join(" "));
+    }
+
+    (found, rest.to_string())
+}
+
+/// The emoji and `:shortcodes:` leading a description, and the rest of it exactly as written.
+#[must_use]
+pub fn leading(description: &str) -> (Vec<&'static str>, &str) {
+    //
+    let mut found = Vec::new();
+    let mut rest = description.trim_start();
+
+    while let Some(word) = rest.split_whitespace().next()
+        && let Some(emoji) = emoji_word(word)
+    {
+        found.extend(emoji);
+        rest = rest[word.len()..].trim_start();
+    }
+
+    (found, rest)
+}
+
+/// The emoji a whole word spells: a single emoji, or `:shortcodes:` such as `:bug::lipstick:`.
+fn emoji_word(word: &str) -> Option<Vec<&'static str>> {
+    //
+    if let Some(codes) = word.strip_prefix(':').and_then(|rest| rest.strip_suffix(':')) {
+        return codes
+            .split("::")
+            .map(|code| emojis::get_by_shortcode(code).map(emojis::Emoji::as_str))
+            .collect();
+    }
+
+    // Symbols such as © and digits only stand for emoji when written with the emoji variation selector.
+    if word.starts_with(is_text_symbol) && !word.contains('\u{fe0f}') {
+        return None;
+    }
+
+    emojis::get(word)
+        .or_else(|| emojis::get(word.trim_end_matches('\u{fe0f}')))
+        .map(|emoji| vec![emoji.as_str()])
+}
+
+/// Whether a character is text by default, though it has an emoji form: ©, ®, ™, digits, `#` and `*`.
+fn is_text_symbol(c: char) -> bool {
+    c.is_ascii() || matches!(c, '©' | '®' | '™')
+}
+
+/// Remove emoji characters, and the joiners and variation selectors between them, from a word.
+fn strip(word: &str) -> String {
+    word.chars()
+        .filter(|&c| {
+            !matches!(c, '\u{200d}' | '\u{fe0f}')
+                && (is_text_symbol(c) || emojis::get(c.encode_utf8(&mut [0; 4])).is_none())
+        })
+        .collect()
+}
+
+/// Split a gitmoji subject such as ":sparkles: Add login" or "✨ Add login" into its emoji and the rest.
+#[must_use]
+pub fn gitmoji_prefix(subject: &str) -> Option<(&'static str, &str)> {
+    //
+    if let Some(rest) = subject.strip_prefix(':') {
+        let (code, rest) = rest.split_once(':')?;
+
+        return Some((emojis::get_by_shortcode(code)?.as_str(), rest.trim_start()));
+    }
+
+    let (first, rest) = subject.split_once(' ').unwrap_or((subject, ""));
+
+    Some((emojis::get(first)?.as_str(), rest.trim_start()))
+}
+
+/// The builtin devmoji table.
+fn devmoji(key: &str) -> Option<&'static str> {
+    match key {
+        "add" => Some("➕"),                                     // heavy_plus_sign
+        "android" => Some("🤖"),                                 // robot
+        "breaking" => Some("💥"),                                // boom
+        "build" | "deps" | "dep" | "dependencies" => Some("📦"), // package
+        "chore" | "maintenance" => Some("🔧"),                   // wrench
+        "ci" | "cd" => Some("👷"),                               // construction_worker
+        "config" => Some("⚙️"),                                  // gear
+        "doc" | "docs" | "documentation" => Some("📚"),          // books
+        "docker" => Some("🐳"),                                  // whale
+        "feat" | "feature" => Some("✨"),                        // sparkles
+        "fix" => Some("🐛"),                                     // bug
+        "i18n" | "l10n" => Some("🌐"),                           // globe_with_meridians
+        "kubernetes" | "k8s" => Some("☸️"),                      // wheel_of_dharma
+        "lint" | "linter" => Some("🚨"),                         // rotating_light
+        "linux" => Some("🐧"),                                   // penguin
+        "macos" | "ios" => Some("🍎"),                           // apple
+        "merge" => Some("🔀"),                                   // twisted_rightwards_arrows
+        "perf" | "performance" => Some("⚡️"),                    // zap
+        "ref" | "refactor" => Some("♻️"),                        // recycle
+        "release" => Some("🚀"),                                 // rocket
+        "remove" => Some("➖"),                                  // heavy_minus_sign
+        "revert" => Some("⏪"),                                  // rewind
+        "security" => Some("🔒"),                                // lock
+        "style" => Some("🎨"),                                   // art
+        "test" | "tests" => Some("✅"),                          // white_check_mark
+        "typo" | "typos" => Some("✏️"),                          // pencil2
+        "ui" | "ux" => Some("💄"),                               // lipstick
+        "windows" => Some("🏁"),                                 // checkered_flag
+        "wip" => Some("🚧"),                                     // construction
+        _ => None,
+    }
+}
+
+/// The gitmoji table, for the conventional types and scopes it has an equivalent for.
+fn gitmoji(key: &str) -> Option<&'static str> {
+    match key {
+        "add" => Some("➕"),                                       // heavy_plus_sign
+        "breaking" => Some("💥"),                                  // boom
+        "build" => Some("📦️"),                                     // package
+        "chore" | "config" => Some("🔧"),                          // wrench
+        "ci" | "cd" => Some("👷"),                                 // construction_worker
+        "deps" | "dep" | "dependencies" | "upgrade" => Some("⬆️"), // arrow_up
+        "docs" | "doc" | "documentation" => Some("📝"),            // memo
+        "docker" => Some("🐳"),                                    // whale
+        "feat" | "feature" => Some("✨"),                          // sparkles
+        "fix" => Some("🐛"),                                       // bug
+        "hotfix" => Some("🚑️"),                                    // ambulance
+        "i18n" | "l10n" => Some("🌐"),                             // globe_with_meridians
+        "init" => Some("🎉"),                                      // tada
+        "lint" | "linter" => Some("🚨"),                           // rotating_light
+        "merge" => Some("🔀"),                                     // twisted_rightwards_arrows
+        "perf" | "performance" => Some("⚡️"),                      // zap
+        "ref" | "refactor" => Some("♻️"),                          // recycle
+        "release" => Some("🔖"),                                   // bookmark
+        "remove" => Some("🔥"),                                    // fire
+        "revert" => Some("⏪️"),                                    // rewind
+        "security" => Some("🔒️"),                                  // lock
+        "style" => Some("🎨"),                                     // art
+        "test" | "tests" => Some("✅"),                            // white_check_mark
+        "typo" | "typos" => Some("✏️"),                            // pencil2
+        "ui" | "ux" => Some("💄"),                                 // lipstick
+        "wip" => Some("🚧"),                                       // construction
+        _ => None,
+    }
+}
+
+/// The minimal table, for the three kinds of change readers care about most.
+fn minimal(key: &str) -> Option<&'static str> {
+    match key {
+        "breaking" => Some("❗"),         // exclamation
+        "feat" | "feature" => Some("✨"), // sparkles
+        "fix" => Some("🐛"),              // bug
+        _ => None,
+    }
+}
+
+/// The corporate-safe table, for release notes read outside engineering.
+fn corporate_safe(key: &str) -> Option<&'static str> {
+    match key {
+        "breaking" => Some("⚠️"),                                // warning
+        "build" | "deps" | "dep" | "dependencies" => Some("📦"), // package
+        "chore" | "maintenance" => Some("🔧"),                   // wrench
+        "ci" | "cd" => Some("⚙️"),                               // gear
+        "doc" | "docs" | "documentation" => Some("📄"),          // page_facing_up
+        "feat" | "feature" => Some("🆕"),                        // new
+        "fix" => Some("🩹"),                                     // adhesive_bandage
+        "perf" | "performance" => Some("📈"),                    // chart_with_upwards_trend
+        "ref" | "refactor" => Some("🔁"),                        // repeat
+        "release" => Some("🏷️"),                                 // label
+        "revert" => Some("↩️"),                                  // leftwards_arrow_with_hook
+        "security" => Some("🔒"),                                // lock
+        "test" | "tests" => Some("✅"),                          // white_check_mark
+        "wip" => Some("🚧"),                                     // construction
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extract_leading_emoji() {
+        assert_eq!(extract("✨ add login"), (vec!["✨"], "add login".to_string()));
+        assert_eq!(
+            extract(":bug::lipstick: :sparkles: fix button"),
+            (vec!["🐛", "💄", "✨"], "fix button".to_string())
+        );
+    }
+
+    #[test]
+    fn extract_leaves_later_emoji_in_place() {
+        assert_eq!(
+            extract("see :smile: docs 🚀 soon"),
+            (Vec::new(), "see :smile: docs 🚀 soon".to_string())
+        );
+    }
+
+    #[test]
+    fn extract_leaves_text_symbols_alone() {
+        assert_eq!(
+            extract("© 2024 notice and ™ mark"),
+            (Vec::new(), "© 2024 notice and ™ mark".to_string())
+        );
+        assert_eq!(extract("#12 and *"), (Vec::new(), "#12 and *".to_string()));
+        assert_eq!(extract("©\u{fe0f} notice").0.len(), 1);
+    }
+
+    #[test]
+    fn extract_keeps_the_rest_as_written() {
+        assert_eq!(extract("✨  two  spaces"), (vec!["✨"], "two  spaces".to_string()));
+    }
+
+    #[test]
+    fn leading_stops_at_unknown_shortcodes() {
+        assert_eq!(leading(":not-an-emoji: text"), (Vec::new(), ":not-an-emoji: text"));
+    }
+}
@@ -0,0 +1,131 @@
+use anyhow::Result;
+use serde_json::Value;
+
+use crate::forge::{self, CheckStatus, PullRequest, PullRequestState};
+use crate::{auth, http};
+
+/// The REST endpoint of a GitHub repository, from its web URL: api.github.com, or `/api/v3` on the configured
+/// GitHub Enterprise instance.
+///
+/// Other hosts have none, rather than being sent requests, and tokens, meant for GitHub.
+fn api_url(repo: &str) -> Option<String> {
+    //
+    if let Some(path) = repo.strip_prefix("https://github.com/") {
+        return Some(format!("https://api.github.com/repos/{path}"));
+    }
+
+    let base = forge::github_url()?;
+    let path = repo.strip_prefix(base)?.strip_prefix('/')?;
+
+    Some(format!("{base}/api/v3/repos/{path}"))
+}
+
+/// GET a document from the GitHub API, authenticated when a token can be found.
+///
+/// Tokens come from `GITHUB_TOKEN` or `GH_TOKEN` for github.com, and `GH_ENTERPRISE_TOKEN` or
+/// `GITHUB_ENTERPRISE_TOKEN` for GitHub Enterprise, then the `gh` CLI's login and git's credential helpers.
+fn get(url: &str) -> Result<Value> {
+    //
+    let host = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let host = match host.split('/').next().unwrap_or_default() {
+        "api.github.com" => "github.com",
+        host => host,
+    };
+
+    let enterprise = forge::github_url()
+        .and_then(|base| base.split_once("://"))
+        .map_or("", |(_, rest)| rest.split('/').next().unwrap_or_default());
+
+    let vars = [
+        ("GITHUB_TOKEN", "github.com"),
+        ("GH_TOKEN", "github.com"),
+        ("GH_ENTERPRISE_TOKEN", enterprise),
+        ("GITHUB_ENTERPRISE_TOKEN", enterprise),
+    ];
+
+    let mut headers = vec![
+        ("Accept", "application/vnd.github+json".to_string()),
+        ("X-GitHub-Api-Version", "2022-11-28".to_string()),
+    ];
+
+    let token = auth::token(host, &vars, &["gh", "auth", "token", "--hostname", host]);
+
+    if let Some(token) = token {
+        headers.push(("Authorization", format!("Bearer {token}")));
+    }
+
+    http::get_json(url, &headers)
+}
+
+/// The pull request a commit in the repository at `repo` was merged through, preferring a merged one.
+pub fn pull_request(repo: &str, sha: &str) -> Result<Option<PullRequest>> {
+    //
+    let Some(api) = api_url(repo) else {
+        return Ok(None);
+    };
+
+    let pulls = get(&format!("{api}/commits/{sha}/pulls"))?;
+    let pulls = pulls.as_array().map(Vec::as_slice).unwrap_or_default();
+
+    let pull = pulls
+        .iter()
+        .find(|pull| !pull["merged_at"].is_null())
+        .or_else(|| pulls.first());
+
+    Ok(pull.and_then(|pull| {
+        Some(PullRequest {
+            number: pull["number"].as_u64()?,
+            title: pull["title"].as_str()?.to_string(),
+            state: match (pull["state"].as_str(), pull["merged_at"].is_null()) {
+                (_, false) => PullRequestState::Merged,
+                (Some("open"), _) => PullRequestState::Open,
+                _ => PullRequestState::Closed,
+            },
+            author: pull["user"]["login"].as_str().map(ToString::to_string),
+            pipeline: None,
+        })
+    }))
+}
+
+/// The combined status of a commit's check runs and legacy commit statuses, or `None` when it has neither.
+pub fn checks(repo: &str, sha: &str) -> Result<Option<CheckStatus>> {
+    //
+    let Some(api) = api_url(repo) else {
+        return Ok(None);
+    };
+
+    let runs = get(&format!("{api}/commits/{sha}/check-runs?per_page=100"))?;
+    let statuses = get(&format!("{api}/commits/{sha}/status"))?;
+
+    let runs = runs["check_runs"].as_array().into_iter().flatten().map(|run| {
+        match (run["status"].as_str(), run["conclusion"].as_str()) {
+            (Some("completed"), Some("success" | "neutral" | "skipped")) => CheckStatus::Success,
+            (Some("completed"), _) => CheckStatus::Failure,
+            _ => CheckStatus::Pending,
+        }
+    });
+
+    let statuses = statuses["statuses"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|status| match status["state"].as_str() {
+            Some("success") => CheckStatus::Success,
+            Some("pending") => CheckStatus::Pending,
+            _ => CheckStatus::Failure,
+        });
+
+    Ok(runs.chain(statuses).max())
+}
+
+/// The GitHub login of a commit's author, when their email belongs to an account.
+pub fn author_login(repo: &str, sha: &str) -> Result<Option<String>> {
+    //
+    let Some(api) = api_url(repo) else {
+        return Ok(None);
+    };
+
+    let commit = get(&format!("{api}/commits/{sha}"))?;
+
+    Ok(commit["author"]["login"].as_str().map(ToString::to_string))
+}
@@ -0,0 +1,163 @@
+use std::sync::OnceLock;
+
+use anyhow::Result;
+use serde_json::Value;
+
+use crate::forge::{self, CheckStatus, PullRequest, PullRequestState};
+use crate::{auth, http};
+
+/// Access token from the `[gitlab]` setting, used in place of `GITLAB_TOKEN`.
+static TOKEN: OnceLock<String> = OnceLock::new();
+
+/// Authenticate with `token` for the rest of the run.
+pub fn set_token(token: String) {
+    let _ = TOKEN.set(token);
+}
+
+/// The base URL of the GitLab instance hosting a project, and the project's path, from its web URL.
+///
+/// Instances served from a subpath, such as `https://corp.example/gitlab`, need their base URL configured.
+fn instance(repo: &str) -> Option<(String, &str)> {
+    //
+    Some(
+        if let Some(base) = forge::gitlab_url()
+            && let Some(path) = repo.strip_prefix(base)
+        {
+            (base.to_string(), path.trim_start_matches('/'))
+        } else {
+            let (scheme, rest) = repo.split_once("://")?;
+            let (host, path) = rest.split_once('/')?;
+
+            (format!("{scheme}://{host}"), path)
+        },
+    )
+}
+
+/// The REST endpoint of a GitLab project, from its web URL.
+fn api_url(repo: &str) -> Option<String> {
+    //
+    let (base, path) = instance(repo)?;
+
+    Some(format!("{base}/api/v4/projects/{}", path.replace('/', "%2F")))
+}
+
+/// GET a document from the GitLab API, authenticated when a token can be found.
+///
+/// Tokens come from the `[gitlab]` setting, `GITLAB_TOKEN` or `GITLAB_ACCESS_TOKEN` for gitlab.com and the configured
+/// instance, the `glab` CLI's login, then git's credential helpers.
+fn get(url: &str) -> Result<Value> {
+    //
+    let host = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let host = host.split('/').next().unwrap_or_default();
+
+    let instance = forge::gitlab_url()
+        .and_then(|base| base.split_once("://"))
+        .map_or("", |(_, rest)| rest.split('/').next().unwrap_or_default());
+
+    let vars = [
+        ("GITLAB_TOKEN", "gitlab.com"),
+        ("GITLAB_ACCESS_TOKEN", "gitlab.com"),
+        ("GITLAB_TOKEN", instance),
+        ("GITLAB_ACCESS_TOKEN", instance),
+    ];
+
+    // The `[gitlab]` token belongs to the configured instance, or to gitlab.com when there is none.
+    let configured = if instance.is_empty() { "gitlab.com" } else { instance };
+
+    let token = TOKEN
+        .get()
+        .filter(|_| host.eq_ignore_ascii_case(configured))
+        .cloned()
+        .or_else(|| auth::token(host, &vars, &["glab", "config", "get", "token", "--host", host]));
+
+    let headers: Vec<_> = token
+        .map(|token| ("Authorization", format!("Bearer {token}")))
+        .into_iter()
+        .collect();
+
+    http::get_json(url, &headers)
+}
+
+/// A pipeline status reported by GitLab, or `None` for a skipped pipeline.
+fn pipeline_status(status: Option<&str>) -> Option<CheckStatus> {
+    match status? {
+        "success" => Some(CheckStatus::Success),
+        "failed" | "canceled" => Some(CheckStatus::Failure),
+        "skipped" => None,
+        _ => Some(CheckStatus::Pending),
+    }
+}
+
+/// The status of the latest pipeline run for a commit, or `None` when it has no pipeline.
+pub fn checks(repo: &str, sha: &str) -> Result<Option<CheckStatus>> {
+    //
+    let Some(api) = api_url(repo) else {
+        return Ok(None);
+    };
+
+    let commit = get(&format!("{api}/repository/commits/{sha}"))?;
+
+    Ok(pipeline_status(commit["last_pipeline"]["status"].as_str()))
+}
+
+/// The merge request a commit was merged through, preferring a merged one.
+fn merged_through(api: &str, sha: &str) -> Result<Option<u64>> {
+    //
+    let merges = get(&format!("{api}/repository/commits/{sha}/merge_requests"))?;
+    let merges = merges.as_array().map(Vec::as_slice).unwrap_or_default();
+
+    let merge = merges
+        .iter()
+        .find(|merge| merge["state"] == "merged")
+        .or_else(|| merges.first());
+
+    Ok(merge.and_then(|merge| merge["iid"].as_u64()))
+}
+
+/// The merge request named by a "See merge request !123" line, or else the one a commit was merged through.
+pub fn merge_request(repo: &str, sha: &str, named: Option<u64>) -> Result<Option<PullRequest>> {
+    //
+    let Some(api) = api_url(repo) else {
+        return Ok(None);
+    };
+
+    let iid = match named {
+        Some(iid) => Some(iid),
+        None => merged_through(&api, sha)?,
+    };
+
+    let Some(iid) = iid else {
+        return Ok(None);
+    };
+
+    // Only a single merge request carries its head pipeline, so it is fetched even when listed above.
+    let merge = get(&format!("{api}/merge_requests/{iid}"))?;
+
+    let Some(title) = merge["title"].as_str() else {
+        return Ok(None);
+    };
+
+    Ok(Some(PullRequest {
+        number: iid,
+        title: title.to_string(),
+        state: match merge["state"].as_str() {
+            Some("merged") => PullRequestState::Merged,
+            Some("opened") => PullRequestState::Open,
+            _ => PullRequestState::Closed,
+        },
+        author: merge["author"]["username"].as_str().map(ToString::to_string),
+        pipeline: pipeline_status(merge["head_pipeline"]["status"].as_str()),
+    }))
+}
+
+/// The username of the GitLab user with a public, or to an administrator any, email address.
+pub fn author_login(repo: &str, email: &str) -> Result<Option<String>> {
+    //
+    let Some((base, _)) = instance(repo) else {
+        return Ok(None);
+    };
+
+    let users = get(&format!("{base}/api/v4/users?search={}", email.replace('+', "%2B")))?;
+
+    Ok(users[0]["username"].as_str().map(ToString::to_string))
+}
@@ -0,0 +1,99 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+
+use crate::commit::Conventional;
+use crate::emoji;
+
+/// Marks a commit-msg hook as ours, so it can be reinstalled without `--force`.
+const MARKER: &str = "# Installed by devmoji-log";
+
+/// Write a commit-msg hook into the repository containing `path`, returning where it was written.
+///
+/// An existing hook that devmoji-log didn't install is only replaced with `force`.
+pub fn install(path: &Path, force: bool) -> Result<PathBuf> {
+    //
+    let repo = git2::Repository::discover(path).context("Not a git repository")?;
+
+    let hooks = match repo.config()?.get_path("core.hooksPath") {
+        Ok(hooks) => repo.workdir().unwrap_or_else(|| repo.path()).join(hooks),
+        Err(_) => repo.path().join("hooks"),
+    };
+
+    let file = hooks.join("commit-msg");
+
+    if !force
+        && let Ok(existing) = std::fs::read_to_string(&file)
+        && !existing.contains(MARKER)
+    {
+        bail!("{} already exists; pass --force to replace it", file.display());
+    }
+
+    std::fs::create_dir_all(&hooks).with_context(|| format!("Creating {}", hooks.display()))?;
+    std::fs::write(
+        &file,
+        format!("#!/bin/sh\n{MARKER}\nexec devmoji-log hook run --file \"$1\"\n"),
+    )
+    .with_context(|| format!("Writing {}", file.display()))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        std::fs::set_permissions(&file, std::fs::Permissions::from_mode(0o755))?;
+    }
+
+    Ok(file)
+}
+
+/// Add emoji to the subject of the commit message in `file`, leaving the body and anything non-conventional alone.
+pub fn run(file: &Path) -> Result<()> {
+    //
+    let message = std::fs::read_to_string(file).with_context(|| format!("Reading {}", file.display()))?;
+
+    if let Some(rewritten) = emojify(&message) {
+        std::fs::write(file, rewritten).with_context(|| format!("Writing {}", file.display()))?;
+    }
+
+    Ok(())
+}
+
+/// The message with emoji added to a conventional subject line, or `None` when there is nothing to change.
+///
+/// The emoji go right after `type(scope)!: `, skipping any already there; the rest of the message is kept exactly as
+/// written, whatever display flags such as `--strip-pr` or `--strip-emoji` say.
+#[must_use]
+pub fn emojify(message: &str) -> Option<String> {
+    //
+    let (subject, rest) = message.split_once('\n').unwrap_or((message, ""));
+    let cc = Conventional::parse(subject)?;
+    let (header, after) = subject.split_once(':')?;
+    let description = after.trim_start();
+    let separator = &after[..after.len() - description.len()];
+
+    let bare = |e: &str| e.trim_end_matches('\u{fe0f}').to_string();
+    let (present, _) = emoji::leading(description);
+    let present: Vec<_> = present.into_iter().map(bare).collect();
+
+    let missing = cc
+        .header_emoji()
+        .split(' ')
+        .filter(|e| !e.is_empty() && !present.contains(&bare(e)))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let missing = emoji::styled(&missing);
+
+    if missing.is_empty() {
+        return None;
+    }
+
+    let separator = if separator.is_empty() { " " } else { separator };
+    let title = format!("{header}:{separator}{missing} {description}");
+
+    Some(if message.contains('\n') {
+        format!("{title}\n{rest}")
+    } else {
+        title
+    })
+}
@@ -0,0 +1,93 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use anyhow::{Context, Result, bail};
+use serde_json::Value;
+
+/// One agent for the whole run, so connections to an API are reused.
+static AGENT: OnceLock<ureq::Agent> = OnceLock::new();
+
+/// Documents already fetched during this run, keyed by URL.
+static RESPONSES: OnceLock<Mutex<HashMap<String, Value>>> = OnceLock::new();
+
+/// Set by `--offline`, so nothing is fetched and enrichment comes from the cache alone.
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+
+/// Keep off the network for the rest of the run.
+pub fn set_offline(offline: bool) {
+    OFFLINE.store(offline, Ordering::Relaxed);
+}
+
+/// Whether the network is off limits.
+pub fn offline() -> bool {
+    OFFLINE.load(Ordering::Relaxed)
+}
+
+/// The agent shared by every request of the run.
+fn agent() -> &'static ureq::Agent {
+    AGENT.get_or_init(|| {
+        ureq::Agent::config_builder()
+            .timeout_global(Some(Duration::from_secs(10)))
+            .user_agent(concat!("devmoji-log/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .into()
+    })
+}
+
+/// GET a JSON document from a forge API with the given headers, remembering it for the rest of the run.
+pub fn get_json(url: &str, headers: &[(&str, String)]) -> Result<Value> {
+    //
+    let responses = RESPONSES.get_or_init(Mutex::default);
+
+    if let Some(value) = responses.lock().ok().and_then(|responses| responses.get(url).cloned()) {
+        return Ok(value);
+    }
+
+    if offline() {
+        bail!("Offline, not fetching {url}");
+    }
+
+    let mut request = agent().get(url);
+
+    for (name, value) in headers {
+        request = request.header(*name, value);
+    }
+
+    let text = request
+        .call()
+        .with_context(|| format!("Fetching {url}"))?
+        .body_mut()
+        .read_to_string()
+        .with_context(|| format!("Reading {url}"))?;
+
+    let value: Value = serde_json::from_str(&text).with_context(|| format!("Parsing {url}"))?;
+
+    if let Ok(mut responses) = responses.lock() {
+        responses.insert(url.to_string(), value.clone());
+    }
+
+    Ok(value)
+}
+
+/// GET a file such as an image, or `None` when the server has nothing at `url`.
+pub fn get_bytes(url: &str) -> Result<Option<Vec<u8>>> {
+    //
+    if offline() {
+        bail!("Offline, not fetching {url}");
+    }
+
+    let mut response = match agent().get(url).call() {
+        Ok(response) => response,
+        Err(ureq::Error::StatusCode(404)) => return Ok(None),
+        Err(e) => return Err(e).with_context(|| format!("Fetching {url}")),
+    };
+
+    let bytes = response
+        .body_mut()
+        .read_to_vec()
+        .with_context(|| format!("Reading {url}"))?;
+
+    Ok(Some(bytes))
+}
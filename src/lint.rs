@@ -0,0 +1,126 @@
+use git_conventional::Commit as ConventionalCommit;
+
+use crate::commit::{self, Commit, Conventional};
+use crate::{emoji, report};
+
+/// Longest subject line, so it fits `git log --oneline` and forge listings without being cut short.
+const MAX_SUBJECT: usize = 72;
+
+/// Ways a commit message breaks the Conventional Commits spec, or the types this repository uses.
+#[must_use]
+pub fn problems(c: &Commit) -> Vec<String> {
+    //
+    let mut problems = Vec::new();
+    let mut lines = c.message.lines();
+    let subject = lines.next().unwrap_or_default();
+
+    // A body run into the subject breaks the whole message, so the subject is checked on its own and the missing
+    // blank line reported rather than a parse error.
+    let cramped = lines.next().is_some_and(|line| !line.trim().is_empty());
+    let message = if cramped { subject } else { c.message.as_str() };
+
+    match ConventionalCommit::parse(message) {
+        Err(e) => problems.push(format!("Not a conventional commit: {e}")),
+        Ok(cc) => {
+            let kind = Conventional::parse(message)
+                .map(|conventional| conventional.canonical_kind().to_string())
+                .unwrap_or_default();
+
+            if !report::is_known_type(&kind)
+                && !commit::is_custom_type(cc.type_().as_str())
+                && !emoji::is_configured(cc.type_().as_str())
+            {
+                problems.push(format!(
+                    "Unknown type \"{}\"; use one of {}, or add it to the types setting",
+                    cc.type_(),
+                    report::known_types().join(", ")
+                ));
+            }
+
+            if let Some(scope) = cc.scope()
+                && !scope
+                    .chars()
+                    .all(|ch| ch.is_ascii_lowercase() || ch.is_ascii_digit() || "-_/.".contains(ch))
+            {
+                problems.push(format!(
+                    "Scope \"{scope}\" should only use lowercase letters, digits, and - _ / ."
+                ));
+            }
+        }
+    }
+
+    let length = subject.chars().count();
+
+    if length > MAX_SUBJECT {
+        problems.push(format!("Subject is {length} characters long, over {MAX_SUBJECT}"));
+    }
+
+    if cramped {
+        problems.push("No blank line between the subject and the body".to_string());
+    }
+
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commit(message: &str) -> Commit {
+        Commit::fixture(message, "2025-01-31T12:00:00[UTC]")
+    }
+
+    #[test]
+    fn conventional_commits_pass() {
+        assert_eq!(problems(&commit("feat(api): add login")), Vec::<String>::new());
+        assert_eq!(
+            problems(&commit(
+                "fix!: drop support\n\nBody text.\n\nBREAKING CHANGE: it's gone"
+            )),
+            Vec::<String>::new()
+        );
+    }
+
+    #[test]
+    fn non_conventional_commits_fail() {
+        let problems = problems(&commit("Add login"));
+
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].starts_with("Not a conventional commit"));
+    }
+
+    #[test]
+    fn unknown_types_fail() {
+        let problems = problems(&commit("hotfix: patch pool"));
+
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].starts_with("Unknown type \"hotfix\""));
+    }
+
+    #[test]
+    fn scopes_must_be_lowercase() {
+        assert_eq!(
+            problems(&commit("fix(API): patch pool")),
+            ["Scope \"API\" should only use lowercase letters, digits, and - _ / ."]
+        );
+        assert_eq!(
+            problems(&commit("fix(crates/parser-v2): patch pool")),
+            Vec::<String>::new()
+        );
+    }
+
+    #[test]
+    fn subjects_must_be_short() {
+        let subject = format!("feat: {}", "a".repeat(70));
+
+        assert_eq!(problems(&commit(&subject)), ["Subject is 76 characters long, over 72"]);
+    }
+
+    #[test]
+    fn bodies_need_a_blank_line() {
+        assert_eq!(
+            problems(&commit("feat: add login\nwith a body")),
+            ["No blank line between the subject and the body"]
+        );
+    }
+}
@@ -1,50 +1,1532 @@
+mod auth;
+mod avatar;
+mod cache;
 mod commit;
+mod config;
+mod date;
+mod emoji;
+mod forge;
+mod github;
+mod gitlab;
+mod hook;
+mod http;
+mod lint;
+mod output;
+mod reflog;
+mod report;
+mod tags;
 
+use std::fs::File;
+use std::io::{self, BufWriter, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, bail};
 use clap::Parser;
+use colored::Colorize;
 use jiff::Zoned;
 use jiff::fmt::friendly::{Designator, Spacing, SpanPrinter};
 
-use crate::commit::Commit;
+use crate::avatar::Graphics;
+use crate::commit::{Commit, DateSource, HyperlinkMode, Query, TrailerFilter, UnbornHead};
+use crate::config::Config;
+use crate::emoji::{EmojiSet, EmojiStyle};
+use crate::forge::Enrichment;
+use crate::output::{Format, Output};
+use crate::report::{Bump, GroupBy, Heatmap, Stats, Tally};
+
+#[derive(Debug, clap::Subcommand)]
+enum Command {
+    /// Show recent movements of HEAD: checkouts, resets, rebases and commits.
+    Reflog,
+
+    /// Show the commits on the current branch that are not on a base branch, like a pull request preview.
+    Diff {
+        #[clap(value_name = "base", help = "Branch or revision to compare against, e.g. main")]
+        base: String,
+    },
+
+    /// Add emoji and highlighting to conventional commit subjects read from stdin, one per line.
+    Format {
+        #[clap(long, value_name = "message", help = "Format this message instead of reading stdin")]
+        message: Option<String>,
+    },
+
+    /// Add emoji to commit messages as they are written, like devmoji's commit-msg hook.
+    Hook {
+        #[clap(subcommand)]
+        action: HookAction,
+    },
+
+    /// List your commits since the previous working day as a bullet list to paste into a standup.
+    Standup,
+
+    /// Report the past week's commits by day and type, to send on by email, as Markdown with `--format markdown`.
+    Report {
+        #[clap(long, help = "Cover the past seven days, which is the default")]
+        week: bool,
+    },
+
+    /// List breaking changes, with the migration notes of their `BREAKING CHANGE:` footers beneath them.
+    Breaking {
+        #[clap(
+            value_name = "revision-range",
+            help = "Range of commits to search, e.g. v1.2.0..v2.0.0"
+        )]
+        range: Option<String>,
+    },
 
+    /// Write a CHANGELOG.md of the history, by release and type, with links to commits and pull requests.
+    Changelog {
+        #[clap(
+            long,
+            value_name = "file",
+            num_args = 0..=1,
+            default_missing_value = "CHANGELOG.md",
+            help = "Add releases newer than those in an existing changelog, and unreleased changes, to its top"
+        )]
+        prepend: Option<PathBuf>,
+    },
+
+    /// Print the semantic version bump the commits since the last release call for: major, minor or patch.
+    ///
+    /// The reasoning, which commits were breaking changes or features, is written to stderr.
+    Bump {
+        #[clap(long, help = "Print the next version instead, such as v1.3.0 after v1.2.0")]
+        next_version: bool,
+    },
+
+    /// Write release notes for the commits between two revisions, by type, with a link comparing them on the forge.
+    ReleaseNotes {
+        #[clap(value_name = "from", help = "The previous release, e.g. v1.3.0")]
+        from: String,
+
+        #[clap(value_name = "to", default_value = "HEAD", help = "The new release, e.g. v1.4.0")]
+        to: String,
+    },
+
+    /// Check commits against the Conventional Commits spec, failing if any don't follow it, e.g. to gate merges in CI.
+    Lint {
+        #[clap(
+            value_name = "revision-range",
+            help = "Range of commits to check, e.g. origin/main..HEAD [default: those since the last release]"
+        )]
+        range: Option<String>,
+    },
+
+    /// Summarize the selected commits: a leaderboard of authors, commits per type and scope, and the busiest day.
+    Stats {
+        #[clap(
+            long,
+            help = "Draw a bar chart of each table, to see at a glance whether a range is fix or feature heavy"
+        )]
+        chart: bool,
+    },
+
+    /// Draw a GitHub-style grid of commits per day over the last weeks, darker on busier days.
+    Heatmap {
+        #[clap(
+            long,
+            value_name = "number",
+            default_value_t = 26,
+            value_parser = clap::value_parser!(u16).range(1..),
+            help = "Number of weeks to show, ending with the current one"
+        )]
+        weeks: u16,
+    },
+
+    /// Manage the cache of pull requests, checks and avatars looked up on forges.
+    Cache {
+        #[clap(subcommand)]
+        action: CacheAction,
+    },
+}
+
+#[derive(Debug, clap::Subcommand)]
+enum CacheAction {
+    /// Delete everything cached, so the next run asks the forges afresh.
+    Clear,
+}
+
+#[derive(Debug, clap::Subcommand)]
+enum HookAction {
+    /// Install a commit-msg hook that runs `devmoji-log hook run` in this repository.
+    Install {
+        #[clap(long, help = "Replace an existing commit-msg hook")]
+        force: bool,
+    },
+
+    /// Add emoji to the subject of a commit message file, as the commit-msg hook does.
+    Run {
+        #[clap(
+            long,
+            value_name = "file",
+            default_value = ".git/COMMIT_EDITMSG",
+            help = "Commit message to rewrite"
+        )]
+        file: PathBuf,
+    },
+}
+
+#[allow(clippy::struct_excessive_bools)]
 #[derive(Debug, clap::Parser)]
 #[clap(
     name = "devmoji-log",
     about = "Show recent Git activity with conventional commit parsing + devmoji ✨"
 )]
 struct Cli {
+    #[clap(subcommand)]
+    command: Option<Command>,
+
+    #[clap(
+        short = 'C',
+        long,
+        global = true,
+        value_name = "path",
+        help = "Run against the repository at this path instead of the current directory (repeatable)"
+    )]
+    repo: Vec<PathBuf>,
+
+    #[clap(
+        value_name = "revision-range",
+        help = "Revision or range of commits to show, e.g. v1.2.0..HEAD or main..feature"
+    )]
+    range: Option<String>,
+
     #[clap(
         short,
+        long,
+        global = true,
+        value_name = "number",
+        help = "Number of commits to retrieve, 0 for all [default: 5, or unlimited with --since/--until and diff]"
+    )]
+    count: Option<usize>,
+
+    #[clap(long, conflicts_with = "count", help = "Show the entire history")]
+    all_commits: bool,
+
+    #[clap(
+        long,
+        value_name = "source",
+        default_value = "committer",
+        help = "Which commit date to show and filter on"
+    )]
+    date_source: DateSource,
+
+    #[clap(
+        long,
+        help = "Mark signed commits with 🔏 when verified, or ❓ when the signature can't be verified"
+    )]
+    show_signatures: bool,
+
+    #[clap(
+        long,
+        help = "Credit each commit's author, linked to their profile when the forge login is known"
+    )]
+    show_author: bool,
+
+    #[clap(
+        long,
+        help = "Show each author's avatar on terminals with kitty, iTerm2 or sixel graphics, or their initials elsewhere"
+    )]
+    avatars: bool,
+
+    #[clap(long, help = "Include a pseudo-entry for uncommitted changes in the working tree")]
+    include_dirty: bool,
+
+    #[clap(long, help = "List commits oldest first")]
+    reverse: bool,
+
+    #[clap(
         long,
         value_name = "number",
-        default_value_t = 5,
-        help = "Number of commits to retrieve"
+        default_value_t = 0,
+        help = "Skip this many commits before listing"
+    )]
+    skip: usize,
+
+    #[clap(
+        short,
+        long,
+        value_name = "name",
+        conflicts_with = "range",
+        help = "Branch to walk instead of HEAD, e.g. origin/main"
+    )]
+    branch: Option<String>,
+
+    #[clap(
+        long,
+        value_name = "ref",
+        help = "Hide commits reachable from this ref, e.g. main (repeatable)"
+    )]
+    not: Vec<String>,
+
+    #[clap(
+        long,
+        value_name = "base",
+        conflicts_with_all = ["range", "all"],
+        help = "Show commits since the current branch forked from a base branch, e.g. main"
+    )]
+    since_branch_point: Option<String>,
+
+    #[clap(
+        short,
+        long,
+        conflicts_with_all = ["range", "branch"],
+        help = "Show activity across every local branch"
+    )]
+    all: bool,
+
+    #[clap(
+        long,
+        conflicts_with_all = ["range", "all"],
+        help = "Show commits since the most recent version tag"
+    )]
+    unreleased: bool,
+
+    #[clap(
+        long,
+        global = true,
+        value_name = "date",
+        value_parser = date::parse,
+        help = "Show commits more recent than a date, e.g. \"2 weeks ago\" or 2025-01-31"
+    )]
+    since: Option<Zoned>,
+
+    #[clap(
+        long,
+        global = true,
+        value_name = "date",
+        value_parser = date::parse_until,
+        help = "Show commits older than a date, e.g. yesterday or 2025-01-31, including all of that day"
+    )]
+    until: Option<Zoned>,
+
+    #[clap(
+        long,
+        global = true,
+        value_name = "pattern",
+        value_parser = regex::Regex::new,
+        help = "Only show commits whose author name or email matches a regex"
+    )]
+    author: Option<regex::Regex>,
+
+    #[clap(
+        long,
+        value_name = "pattern",
+        value_parser = regex::Regex::new,
+        help = "Only show commits whose message matches a regex"
+    )]
+    grep: Option<regex::Regex>,
+
+    #[clap(
+        short,
+        long = "type",
+        global = true,
+        value_name = "types",
+        value_delimiter = ',',
+        help = "Only show conventional commits of these comma-separated types, e.g. feat,fix"
+    )]
+    types: Vec<String>,
+
+    #[clap(
+        short,
+        long = "scope",
+        global = true,
+        value_name = "scopes",
+        value_delimiter = ',',
+        value_parser = commit::glob,
+        help = "Only show conventional commits with these comma-separated scopes; globs like \"ui*\" are allowed"
+    )]
+    scopes: Vec<regex::Regex>,
+
+    #[clap(
+        long,
+        help = "Only show breaking changes, marked with `!` or a BREAKING CHANGE footer"
+    )]
+    breaking_only: bool,
+
+    #[clap(long, global = true, help = "Hide commits by bots such as dependabot and renovate")]
+    no_bots: bool,
+
+    #[clap(
+        long = "trailer",
+        value_name = "key[=value]",
+        value_parser = TrailerFilter::parse,
+        help = "Only show commits carrying a trailer, e.g. Reviewed-by=alice (repeatable)"
     )]
-    count: usize,
+    trailers: Vec<TrailerFilter>,
+
+    #[clap(long, conflicts_with = "merges_only", help = "Hide merge commits")]
+    no_merges: bool,
+
+    #[clap(long, help = "Only show merge commits")]
+    merges_only: bool,
+
+    #[clap(
+        long,
+        help = "Only follow the first parent of merge commits, showing one entry per merged branch"
+    )]
+    first_parent: bool,
+
+    #[clap(
+        long,
+        global = true,
+        value_name = "name",
+        help = "Remote used to build commit links [default: origin, upstream, or the first remote]"
+    )]
+    remote: Option<String>,
+
+    #[clap(long, global = true, help = "Show full 40 character commit hashes")]
+    full_hash: bool,
+
+    #[clap(
+        long,
+        global = true,
+        value_name = "n",
+        conflicts_with = "full_hash",
+        help = "Abbreviate commit hashes to n characters [default: git's core.abbrev]"
+    )]
+    abbrev: Option<usize>,
+
+    #[clap(
+        long,
+        conflicts_with_all = ["range", "branch", "all"],
+        help = "List recent commits of every worktree, grouped under its path and branch"
+    )]
+    worktrees: bool,
+
+    #[clap(long, help = "Also list recent commits of each submodule, grouped under its path")]
+    recurse_submodules: bool,
+
+    #[clap(
+        short,
+        long,
+        global = true,
+        value_name = "format",
+        help = "Output format [default: text, or inferred from the --output file's extension]"
+    )]
+    format: Option<Format>,
+
+    #[clap(
+        short,
+        long = "output",
+        global = true,
+        value_name = "file",
+        help = "Write to a file instead of standard output"
+    )]
+    output_file: Option<PathBuf>,
+
+    #[clap(
+        long,
+        value_name = "file",
+        help = "Render commits through a Tera template instead of --format"
+    )]
+    template: Option<PathBuf>,
+
+    #[clap(
+        long,
+        value_name = "columns",
+        help = "Truncate lines to a width [default: the terminal's, 0 = unlimited]"
+    )]
+    width: Option<usize>,
+
+    #[clap(
+        long,
+        global = true,
+        visible_alias = "emoji-theme",
+        value_name = "set",
+        help = "Emoji conventions to render commits with [default: devmoji]"
+    )]
+    emoji_set: Option<EmojiSet>,
+
+    #[clap(
+        long,
+        global = true,
+        help = "Remove emoji and :shortcodes: written into commit descriptions"
+    )]
+    strip_emoji: bool,
+
+    #[clap(
+        long,
+        global = true,
+        help = "Remove the trailing (#123) pull request number of squash merges"
+    )]
+    strip_pr: bool,
+
+    #[clap(
+        long,
+        global = true,
+        value_name = "when",
+        default_value = "auto",
+        help = "Write clickable OSC-8 hyperlinks, or spell out URLs after each commit instead"
+    )]
+    hyperlinks: HyperlinkMode,
+
+    #[clap(long, global = true, help = "Write emoji as :shortcodes:, for GitHub and Slack")]
+    shortcodes: bool,
+
+    #[clap(
+        long,
+        global = true,
+        conflicts_with = "shortcodes",
+        help = "Show colored [type] badges instead of emoji"
+    )]
+    no_emoji: bool,
+
+    #[clap(
+        long,
+        value_name = "forge",
+        help = "Look up the pull request each commit was merged through, using GITHUB_TOKEN, gh's login or GITLAB_TOKEN"
+    )]
+    enrich: Option<Enrichment>,
+
+    #[clap(
+        long,
+        help = "Mark each commit with its CI status from GitHub or GitLab: ✅ passed, ❌ failed, 🟡 pending"
+    )]
+    checks: bool,
+
+    #[clap(
+        long,
+        global = true,
+        help = "Ask forges afresh rather than using cached pull requests, checks and avatars"
+    )]
+    no_cache: bool,
+
+    #[clap(
+        long,
+        global = true,
+        help = "Stay off the network, showing only cached pull requests, checks and avatars"
+    )]
+    offline: bool,
+
+    #[clap(long, help = "Show files changed, insertions and deletions of each commit")]
+    stat: bool,
+
+    #[clap(long, help = "Show a compact ++++-- bar of each commit's insertions and deletions")]
+    stat_graph: bool,
+
+    #[clap(long, help = "Print the body and footers of each commit beneath it")]
+    body: bool,
+
+    #[clap(
+        long,
+        help = "Print why each commit marked ⚠️ doesn't parse as a conventional commit beneath it"
+    )]
+    strict: bool,
+
+    #[clap(
+        short = 'z',
+        long,
+        conflicts_with_all = ["format", "pretty", "template"],
+        help = "Print NUL delimited hash and message records, for scripts"
+    )]
+    null: bool,
+
+    #[clap(
+        long,
+        global = true,
+        help = "Print without colors, hyperlinks or the heading, e.g. for grep"
+    )]
+    plain: bool,
+
+    #[clap(
+        long,
+        value_name = "format",
+        conflicts_with_all = ["format", "template"],
+        help = "Print each commit with placeholders: %h %H %e %t %S %s %b %ar %ad %an %ae %n %%"
+    )]
+    pretty: Option<String>,
+
+    #[clap(
+        long,
+        help = "After the list, sum up its commits, authors, days and conventional types"
+    )]
+    summary: bool,
+
+    #[clap(long, value_name = "key", help = "Group the output into sections")]
+    group_by: Option<GroupBy>,
+
+    #[clap(
+        long,
+        value_name = "path",
+        help = "Only show commits touching the given path (repeatable)"
+    )]
+    path: Vec<String>,
+
+    #[clap(
+        long,
+        global = true,
+        value_name = "path",
+        help = "Only include commits touching a monorepo package's subtree or scoped to its name, e.g. crates/parser"
+    )]
+    package: Option<String>,
+
+    #[clap(
+        last = true,
+        value_name = "path",
+        help = "Only show commits touching the given paths"
+    )]
+    pathspec: Vec<String>,
+}
+
+impl Cli {
+    /// The `--format`, or one inferred from the extension of the `--output` file.
+    fn format(&self) -> Format {
+        //
+        let extension = self
+            .output_file
+            .as_ref()
+            .and_then(|path| path.extension())
+            .and_then(|extension| extension.to_str())
+            .map(str::to_lowercase);
+
+        self.format.unwrap_or(match extension.as_deref() {
+            Some("md" | "markdown") => Format::Markdown,
+            Some("json") => Format::Json,
+            Some("jsonl" | "ndjson") => Format::Jsonl,
+            Some("html" | "htm") => Format::Html,
+            Some("csv") => Format::Csv,
+            Some("tsv") => Format::Tsv,
+            Some("atom" | "xml") => Format::Atom,
+            _ if matches!(self.command, Some(Command::Standup)) => Format::Markdown,
+            _ => Format::Text,
+        })
+    }
+
+    /// Where the listing is written: the `--output` file, or standard output.
+    fn writer(&self) -> anyhow::Result<Box<dyn Write>> {
+        Ok(match &self.output_file {
+            Some(path) => Box::new(BufWriter::new(
+                File::create(path).with_context(|| format!("Unable to create {}", path.display()))?,
+            )),
+            None => Box::new(io::stdout().lock()),
+        })
+    }
+
+    /// Length to abbreviate commit hashes to, if not git's default.
+    fn abbrev(&self) -> Option<usize> {
+        if self.full_hash { Some(40) } else { self.abbrev }
+    }
+
+    /// How emoji are written out, from `--shortcodes` and `--no-emoji`.
+    fn emoji_style(&self) -> EmojiStyle {
+        if self.no_emoji {
+            EmojiStyle::Badges
+        } else if self.shortcodes {
+            EmojiStyle::Shortcodes
+        } else {
+            EmojiStyle::Unicode
+        }
+    }
+
+    /// Set up colors, emoji and link rendering for the rest of the run from the flags and the config.
+    fn install_rendering(&self, config: &Config) -> anyhow::Result<()> {
+        //
+        if self.escapes_unwanted() || !io::stdout().is_terminal() {
+            colored::control::set_override(false);
+        }
+
+        commit::set_hyperlinks(self.hyperlinks());
+        emoji::set_custom_emoji(config.emoji_table());
+        emoji::set_scope_emoji(config.scope_table());
+        emoji::set_strip_emoji(self.strip_emoji);
+        commit::set_strip_pull_requests(self.strip_pr);
+
+        if let Some(fallback) = config.fallback_emoji() {
+            emoji::set_fallback_emoji(fallback);
+        }
+
+        commit::set_type_aliases(config.aliases.clone());
+        commit::set_custom_types(config.types.clone());
+        emoji::set_emoji_set(self.emoji_set.or(config.emoji_set).unwrap_or_default());
+        emoji::set_emoji_style(self.emoji_style());
+
+        if let Some(template) = &config.commit_url {
+            forge::set_commit_url(template.clone());
+        }
+
+        if self.avatars
+            && !self.escapes_unwanted()
+            && io::stdout().is_terminal()
+            && let Some(graphics) = Graphics::detect()
+        {
+            avatar::set_graphics(graphics);
+        }
+
+        if let Some((pattern, url)) = config.tracker()? {
+            commit::set_tracker(pattern, url);
+        }
+
+        if let Some(gitlab) = &config.gitlab {
+            if let Some(url) = &gitlab.url {
+                forge::set_gitlab_url(url);
+            }
+
+            if let Some(token) = &gitlab.token {
+                gitlab::set_token(token.clone());
+            }
+        }
+
+        if let Some(url) = config.github.as_ref().and_then(|github| github.url.as_ref()) {
+            forge::set_github_url(url);
+        }
+
+        Ok(())
+    }
+
+    /// Whether escape codes would be noise: everything but text on a terminal is pasted or piped elsewhere.
+    fn escapes_unwanted(&self) -> bool {
+        self.plain || self.null || self.format() != Format::Text || self.output_file.is_some()
+    }
+
+    /// Whether OSC-8 hyperlinks are written, from `--hyperlinks` and what the terminal supports.
+    fn hyperlinks(&self) -> bool {
+        //
+        if self.escapes_unwanted() {
+            return false;
+        }
+
+        match self.hyperlinks {
+            HyperlinkMode::Always => true,
+            HyperlinkMode::Never => false,
+            HyperlinkMode::Auto => io::stdout().is_terminal() && commit::supports_hyperlinks(),
+        }
+    }
+
+    /// Whether commit URLs are spelled out after each line, in place of hyperlinks the terminal can't show.
+    fn urls(&self) -> bool {
+        !self.escapes_unwanted()
+            && !self.hyperlinks()
+            && (self.hyperlinks == HyperlinkMode::Never || io::stdout().is_terminal())
+    }
+
+    /// How the listing is rendered, from `--format`, `--pretty`, `--plain`, `--width`, `--body`, `--stat` and `--null`.
+    fn output<'a>(&'a self, now: &'a Zoned, printer: &'a SpanPrinter) -> Output<'a> {
+        Output::new(self.format(), now, printer)
+            .pretty(self.pretty.as_deref())
+            .plain(self.plain)
+            .width(self.width())
+            .body(self.body)
+            .breaking_notes(matches!(self.command, Some(Command::Breaking { .. })))
+            .strict(self.strict)
+            .stats(self.stat, self.stat_graph)
+            .null(self.null)
+            .urls(self.urls())
+            .authors(self.show_author)
+            .avatars(self.avatars)
+    }
+
+    /// Columns to truncate lines to: `--width`, or the terminal's when printing to one.
+    fn width(&self) -> Option<usize> {
+        match self.width {
+            Some(0) => None,
+            Some(width) => Some(width),
+            None => terminal_size::terminal_size().map(|(terminal_size::Width(width), _)| usize::from(width)),
+        }
+    }
+
+    /// Whether a subcommand summarizes the selected commits, which then default to the whole history.
+    fn summarizes(&self) -> bool {
+        matches!(
+            self.command,
+            Some(
+                Command::Stats { .. }
+                    | Command::Heatmap { .. }
+                    | Command::Report { .. }
+                    | Command::Breaking { .. }
+                    | Command::Changelog { .. }
+                    | Command::Bump { .. }
+                    | Command::ReleaseNotes { .. }
+                    | Command::Lint { .. }
+            )
+        )
+    }
+
+    /// Whether the listing is of your commits since the previous working day.
+    fn standup(&self) -> bool {
+        matches!(self.command, Some(Command::Standup))
+    }
+
+    /// The heading above the listing: "Since Friday" for a standup, or else "Recent Activity".
+    fn heading(&self, query: &Query, now: &Zoned) -> String {
+        //
+        let Some(since) = query.since.as_ref().filter(|_| self.standup()) else {
+            return "Recent Activity".to_string();
+        };
+
+        let days = now
+            .date()
+            .since(since.date())
+            .map(|span| span.get_days())
+            .unwrap_or_default();
+
+        match days {
+            1 => "Since yesterday".to_string(),
+            2..=6 => format!("Since {}", since.strftime("%A")),
+            _ => format!("Since {}", since.strftime("%A, %-d %B")),
+        }
+    }
+
+    /// Your commits, by the `user.email` git commits as, unless `--author` picks someone else.
+    fn standup_author(&self, path: &Path) -> anyhow::Result<Option<regex::Regex>> {
+        //
+        if let Some(author) = &self.author {
+            return Ok(Some(author.clone()));
+        }
+
+        let Some(email) = commit::user_email(path)? else {
+            bail!("Set git's user.email, or pass --author, to list your commits for a standup");
+        };
+
+        Ok(Some(regex::Regex::new(&format!("(?i)<{}>", regex::escape(&email)))?))
+    }
+
+    fn query(&self, config: &Config) -> anyhow::Result<Query> {
+        //
+        // A branch preview shows the whole branch, as `--since-branch-point` does.
+        let bounded = self.unreleased
+            || self.since_branch_point.is_some()
+            || self.since.is_some()
+            || self.until.is_some()
+            || matches!(self.command, Some(Command::Diff { .. }));
+
+        let count = match self.count {
+            Some(0) => usize::MAX,
+            Some(count) => count,
+            None if bounded || self.all_commits || self.summarizes() || self.standup() => usize::MAX,
+            None => 5,
+        };
+
+        Ok(Query {
+            types: self.types.clone(),
+            scopes: self.scopes.clone(),
+            breaking_only: self.breaking_only || matches!(self.command, Some(Command::Breaking { .. })),
+            trailers: self.trailers.clone(),
+            signatures: self.show_signatures,
+            date_source: self.date_source,
+            bots: if self.no_bots {
+                config.bot_patterns()?
+            } else {
+                Vec::new()
+            },
+            count,
+            skip: self.skip,
+            range: match &self.command {
+                Some(Command::Breaking { range: Some(range) } | Command::Lint { range: Some(range) }) => {
+                    Some(range.clone())
+                }
+                Some(Command::ReleaseNotes { from, to }) => Some(format!("{from}..{to}")),
+                _ => self.range.clone(),
+            },
+            branch: self.branch.clone(),
+            branch_point: self.since_branch_point.clone(),
+            not: match &self.command {
+                Some(Command::Diff { base }) => self.not.iter().chain([base]).cloned().collect(),
+                _ => self.not.clone(),
+            },
+            all: self.all,
+            since: match &self.since {
+                None if self.standup() => Some(date::previous_working_day(&Zoned::now())?),
+                since => since.clone(),
+            },
+            until: self.until.clone(),
+            author: self.author.clone(),
+            grep: self.grep.clone(),
+            no_merges: self.no_merges || matches!(self.command, Some(Command::Lint { .. })),
+            merges_only: self.merges_only,
+            first_parent: self.first_parent,
+            unreleased: self.unreleased
+                || matches!(self.command, Some(Command::Bump { .. } | Command::Lint { range: None })),
+            releases: self.group_by == Some(GroupBy::Release)
+                || self.format() == Format::Keepachangelog
+                || matches!(self.command, Some(Command::Changelog { .. })),
+            remote: self.remote.clone(),
+            abbrev: self.abbrev(),
+            stats: self.stat || self.stat_graph || matches!(self.command, Some(Command::Stats { .. })),
+            enrich: self.enrich,
+            checks: self.checks,
+            logins: (self.show_author || self.avatars) && self.enrich.is_some(),
+            paths: self.path.iter().chain(&self.pathspec).cloned().collect(),
+            package: self.package.clone(),
+        })
+    }
 }
 
 pub fn main() -> anyhow::Result<()> {
     let cli = Cli::parse();
+    let mut out = cli.writer()?;
+
+    match run(&cli, &mut out).and_then(|()| Ok(out.flush()?)) {
+        // A reader such as `head` going away early is not an error.
+        Err(e)
+            if e.downcast_ref::<io::Error>()
+                .is_some_and(|e| e.kind() == io::ErrorKind::BrokenPipe) =>
+        {
+            Ok(())
+        }
+        result => result,
+    }
+}
+
+/// Printer for relative ages such as "2 days, 3 hours ago".
+fn span_printer() -> SpanPrinter {
+    SpanPrinter::new()
+        .direction(jiff::fmt::friendly::Direction::Suffix)
+        .spacing(Spacing::BetweenUnitsAndDesignators)
+        .comma_after_designator(true)
+        .designator(Designator::Verbose)
+}
 
+/// List recent activity, writing it to `out`.
+fn run(cli: &Cli, out: &mut dyn Write) -> anyhow::Result<()> {
+    //
+    let format = cli.format();
     let now = Zoned::now();
-    let commits = Commit::last_n_commits(cli.count)?;
+    let paths = if cli.repo.is_empty() {
+        vec![std::env::current_dir()?]
+    } else {
+        cli.repo.clone()
+    };
 
-    if !commits.is_empty() {
-        //
-        let printer = SpanPrinter::new()
-            .direction(jiff::fmt::friendly::Direction::Suffix)
-            .spacing(Spacing::BetweenUnitsAndDesignators)
-            .comma_after_designator(true)
-            .designator(Designator::Verbose);
+    let printer = span_printer();
+
+    cache::set_bypass(cli.no_cache);
+    http::set_offline(cli.offline);
+
+    let config = Config::load(&paths[0])?;
+
+    cli.install_rendering(&config)?;
+
+    match &cli.command {
+        Some(Command::Hook { action }) => return run_hook(out, action, &paths[0]),
+        Some(Command::Cache { action }) => return run_cache(out, action),
+        Some(Command::Format { message }) => return format_messages(out, message.as_deref()),
+        _ => {}
+    }
+
+    let output = cli.output(&now, &printer);
+
+    if let Some(Command::Reflog) = cli.command {
+        return print_reflog(out, cli, &output, &paths);
+    }
+
+    let mut query = cli.query(&config)?;
+
+    if cli.standup() {
+        query.author = cli.standup_author(&paths[0])?;
+    }
+
+    if cli.summarizes() {
+        return print_summary(out, cli, &output, &paths, &query, &now);
+    }
+
+    let dirty = if cli.include_dirty && cli.template.is_none() && !output.is_structured() {
+        Some(commit::dirty_files(&paths[0])?)
+            .filter(|files| *files > 0)
+            .map(|files| output.dirty(files))
+    } else {
+        None
+    };
+
+    // An unbounded listing of a single repository is printed as it is walked, rather than buffered.
+    if let [path] = paths.as_slice()
+        && query.count == usize::MAX
+        && format.is_streamable()
+        && cli.template.is_none()
+        && cli.group_by.is_none()
+        && !cli.reverse
+        && !cli.worktrees
+        && !cli.recurse_submodules
+    {
+        let heading = cli.heading(&query, &now);
+
+        return stream_activity(out, path, &query, dirty.as_deref(), &output, &heading, cli.summary);
+    }
+
+    // Every worktree is listed in its own section, including the main one.
+    let mut commits = if cli.worktrees {
+        Vec::new()
+    } else {
+        match Commit::query_all(&paths, &query) {
+            Err(e) if e.is::<UnbornHead>() => {
+                writeln!(out, "  {e}")?;
+                return Ok(());
+            }
+            commits => commits?,
+        }
+    };
+
+    let mut extra = Vec::new();
+
+    if cli.worktrees {
+        extra.extend(worktree_activity(&paths, &query)?);
+    }
+
+    if cli.recurse_submodules {
+        extra.extend(submodule_activity(&paths, &query)?);
+    }
+
+    // The newest commits are selected first, then listed chronologically.
+    if cli.reverse {
+        commits.reverse();
+
+        for (_, commits) in &mut extra {
+            commits.reverse();
+        }
+    }
+
+    if cli.template.is_some() || output.is_structured() {
+        commits.extend(extra.into_iter().flat_map(|(_, commits)| commits));
+        return print_structured(out, &output, format, cli.template.as_deref(), &commits);
+    }
+
+    print_listing(out, cli, &output, &cli.heading(&query, &now), commits, &extra, dirty)
+}
+
+/// Print the buffered listing: uncommitted work and commits, in sections when grouped, then those of each extra
+/// section such as a worktree, and the `--summary` line.
+fn print_listing(
+    out: &mut dyn Write,
+    cli: &Cli,
+    output: &Output<'_>,
+    heading: &str,
+    commits: Vec<Commit>,
+    extra: &[(String, Vec<Commit>)],
+    dirty: Option<String>,
+) -> anyhow::Result<()> {
+    //
+    if commits.is_empty() && extra.iter().all(|(_, commits)| commits.is_empty()) && dirty.is_none() {
+        return Ok(());
+    }
+
+    print_heading(out, output, heading)?;
+
+    let tally = Tally::of(
+        commits.iter().chain(extra.iter().flat_map(|(_, commits)| commits)),
+        output.now(),
+    );
+
+    if let Some(group_by) = cli.group_by {
+        print_section(out, output, None, dirty.as_slice())?;
+
+        for (title, commits) in report::sections(commits, group_by, output.now()) {
+            print_section(out, output, Some(&title), &output.lines(&commits)?)?;
+        }
+    } else {
+        let mut lines = output.lines(&commits)?;
+
+        // Uncommitted work is the newest entry, so it goes wherever the newest commit is listed.
+        if let Some(dirty) = dirty {
+            if cli.reverse {
+                lines.push(dirty);
+            } else {
+                lines.insert(0, dirty);
+            }
+        }
+
+        print_section(out, output, None, &lines)?;
+    }
+
+    for (title, commits) in extra {
+        print_section(out, output, Some(title), &output.lines(commits)?)?;
+    }
+
+    if cli.summary {
+        print_summary_line(out, output, &tally)?;
+    }
+
+    Ok(())
+}
+
+/// Print the activity of a single repository as it is walked, rather than buffering it.
+fn stream_activity(
+    out: &mut dyn Write,
+    path: &Path,
+    query: &Query,
+    dirty: Option<&str>,
+    output: &Output<'_>,
+    heading: &str,
+    summary: bool,
+) -> anyhow::Result<()> {
+    //
+    let mut listed = false;
+    let mut tally = Tally::new(output.now());
+
+    if let Some(dirty) = dirty {
+        print_heading(out, output, heading)?;
+        writeln!(out, "{dirty}")?;
+        listed = true;
+    }
+
+    let walked = Commit::walk(path, query, |c| {
+        if !listed && !output.is_structured() {
+            print_heading(out, output, heading)?;
+            listed = true;
+        }
+
+        write!(out, "{}{}", output.line(&c)?, output.terminator())?;
+        tally.add(&c);
+        Ok(())
+    });
+
+    match walked {
+        Err(e) if e.is::<UnbornHead>() => {
+            writeln!(out, "  {e}")?;
+            Ok(())
+        }
+        walked => {
+            if listed {
+                writeln!(out)?;
+            }
+
+            if summary && !output.is_structured() {
+                print_summary_line(out, output, &tally)?;
+            }
+
+            walked
+        }
+    }
+}
+
+/// Print commits through a template, as a single document, or one JSON object per line.
+fn print_structured(
+    out: &mut dyn Write,
+    output: &Output<'_>,
+    format: Format,
+    template: Option<&Path>,
+    commits: &[Commit],
+) -> anyhow::Result<()> {
+    //
+    if let Some(template) = template {
+        let source = std::fs::read_to_string(template)
+            .with_context(|| format!("Unable to read template {}", template.display()))?;
+
+        write!(out, "{}", output.template(&source, commits)?)?;
+        return Ok(());
+    }
+
+    match format {
+        Format::Json => writeln!(out, "{}", output.json(commits)?)?,
+        Format::Html => write!(out, "{}", output::html(commits))?,
+        Format::Atom => write!(out, "{}", output.atom(commits))?,
+        Format::Keepachangelog => write!(out, "{}", output::keepachangelog(commits))?,
+        Format::GhRelease => write!(out, "{}", output::gh_release(commits))?,
+        Format::Csv => write!(out, "{}", output::delimited(commits, b',')?)?,
+        Format::Tsv => write!(out, "{}", output::delimited(commits, b'\t')?)?,
+        _ => {
+            for line in output.lines(commits)? {
+                write!(out, "{line}{}", output.terminator())?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Print each line of `message`, or of stdin, as a formatted commit subject.
+fn format_messages(out: &mut dyn Write, message: Option<&str>) -> anyhow::Result<()> {
+    //
+    let input = match message {
+        Some(message) => message.to_string(),
+        None => io::read_to_string(io::stdin())?,
+    };
+
+    for line in input.lines() {
+        writeln!(out, "{}", commit::format_message(line))?;
+    }
+
+    Ok(())
+}
 
-        println!("  ## Recent Activity");
-        println!();
+/// Install or run the commit-msg hook.
+fn run_hook(out: &mut dyn Write, action: &HookAction, path: &Path) -> anyhow::Result<()> {
+    match action {
+        HookAction::Install { force } => {
+            let file = hook::install(path, *force)?;
 
-        for c in commits {
-            println!("  * {} {}", c.id(), c.format(&now, &printer)?);
+            writeln!(out, "Installed {}", file.display())?;
+        }
+        HookAction::Run { file } => hook::run(file)?,
+    }
+
+    Ok(())
+}
+
+/// Print a summary of the selected commits, as tables or JSON.
+fn print_summary(
+    out: &mut dyn Write,
+    cli: &Cli,
+    output: &Output<'_>,
+    paths: &[PathBuf],
+    query: &Query,
+    now: &Zoned,
+) -> anyhow::Result<()> {
+    //
+    if let Some(Command::Changelog { prepend: Some(file) }) = &cli.command {
+        return prepend_changelog(out, &paths[0], query, file);
+    }
+
+    let mut query = query.clone();
+
+    // A heatmap or report only needs the commits of the weeks it covers.
+    if query.since.is_none() {
+        query.since = match cli.command {
+            Some(Command::Heatmap { weeks }) => Some(Heatmap::start(weeks, now)?),
+            Some(Command::Report { .. }) => Some(report::week_start(now)?),
+            _ => None,
+        };
+    }
+
+    let commits = match Commit::query_all(paths, &query) {
+        Err(e) if e.is::<UnbornHead>() => {
+            writeln!(out, "  {e}")?;
+            return Ok(());
+        }
+        commits => commits?,
+    };
+
+    match cli.command {
+        Some(Command::Stats { chart }) => {
+            let stats = Stats::new(&commits, now);
+
+            if cli.format() == Format::Json {
+                writeln!(out, "{}", serde_json::to_string_pretty(&stats)?)?;
+            } else {
+                print_heading(out, output, "Stats")?;
+                write!(out, "{}", stats.render(output, chart))?;
+            }
+        }
+        Some(Command::Heatmap { weeks }) => {
+            let heatmap = Heatmap::new(&commits, weeks, now)?;
+
+            if cli.format() == Format::Json {
+                writeln!(out, "{}", serde_json::to_string_pretty(&heatmap)?)?;
+            } else {
+                print_heading(out, output, "Activity")?;
+                write!(out, "{}", heatmap.render(output))?;
+            }
+        }
+        Some(Command::Breaking { .. } | Command::Report { .. }) if output.is_structured() => {
+            print_structured(out, output, cli.format(), None, &commits)?;
+        }
+        Some(Command::Changelog { .. }) => write!(out, "{}", report::changelog(commits))?,
+        Some(Command::Lint { .. }) => print_lint(out, output, &commits)?,
+        Some(Command::ReleaseNotes { ref from, ref to }) => {
+            write!(out, "{}", report::release_notes(commits, from, to))?;
+        }
+        Some(Command::Bump { next_version }) => print_bump(out, output, &paths[0], &commits, next_version)?,
+        Some(Command::Breaking { .. }) => {
+            print_heading(out, output, "Breaking Changes")?;
+
+            if commits.is_empty() {
+                writeln!(out, "{}No breaking changes", output.indent())?;
+                writeln!(out)?;
+            } else {
+                print_section(out, output, None, &output.lines(&commits)?)?;
+            }
+        }
+        Some(Command::Report { .. }) => {
+            let since = query.since.as_ref().unwrap_or(now);
+
+            print_heading(
+                out,
+                output,
+                &format!(
+                    "Weekly Report: {} – {}",
+                    since.strftime("%-d %B"),
+                    now.strftime("%-d %B")
+                ),
+            )?;
+            print_report(out, output, commits)?;
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+/// Print each commit that doesn't follow the Conventional Commits spec with its problems, failing if there are any.
+fn print_lint(out: &mut dyn Write, output: &Output<'_>, commits: &[Commit]) -> anyhow::Result<()> {
+    //
+    let indent = output.indent();
+    let mut failed = 0;
+
+    print_heading(out, output, "Lint")?;
+
+    for c in commits {
+        let problems = lint::problems(c);
+
+        if problems.is_empty() {
+            continue;
         }
 
-        println!();
+        failed += 1;
+        writeln!(out, "{}", output.line(c)?)?;
+
+        for problem in problems {
+            writeln!(out, "{indent}  {} {problem}", "✗".red())?;
+        }
+
+        writeln!(out)?;
+    }
+
+    if failed > 0 {
+        bail!(
+            "{failed} of {} commits don't follow Conventional Commits",
+            commits.len()
+        );
     }
 
+    let passed = match commits.len() {
+        1 => "The commit follows".to_string(),
+        count => format!("All {count} commits follow"),
+    };
+
+    writeln!(out, "{indent}{} {passed} Conventional Commits", "✓".green())?;
+
     Ok(())
 }
+
+/// Print the bump the commits since the last release call for, or the version it leads to, explaining why on stderr.
+fn print_bump(
+    out: &mut dyn Write,
+    output: &Output<'_>,
+    path: &Path,
+    commits: &[Commit],
+    next_version: bool,
+) -> anyhow::Result<()> {
+    //
+    let repo = git2::Repository::discover(path)?;
+    let release = tags::latest_release(&repo, repo.head()?.peel_to_commit()?.id())?;
+    let since = release
+        .as_ref()
+        .map_or_else(|| "the first commit".to_string(), |release| release.name.clone());
+
+    let Some(bump) = commits.iter().map(Bump::of).max() else {
+        eprintln!("No commits since {since}, so nothing to release");
+        return Ok(());
+    };
+
+    // The name printed is the bump made to the version, so scripts reading either agree.
+    let made = release.as_ref().map_or(bump, |release| bump.at(&release.version));
+
+    if next_version {
+        let next = match &release {
+            // The tag's prefix, such as `v` or `my-app/`, is kept.
+            Some(release) => {
+                let prefix = release.name.strip_suffix(&release.version.to_string()).unwrap_or("v");
+
+                format!("{prefix}{}", bump.apply(&release.version))
+            }
+            None => "v0.1.0".to_string(),
+        };
+
+        writeln!(out, "{next}")?;
+    } else {
+        writeln!(out, "{}", made.name())?;
+    }
+
+    let reasons: Vec<_> = commits.iter().filter(|c| Bump::of(c) == bump).collect();
+    let why = match bump {
+        Bump::Major => "breaking change",
+        Bump::Minor => "feature",
+        Bump::Patch => "other change",
+    };
+
+    eprintln!(
+        "{}: {} {why}{} since {since}{}",
+        made.name(),
+        reasons.len(),
+        if reasons.len() == 1 { "" } else { "s" },
+        if made == bump {
+            ""
+        } else {
+            " (breaking changes only bump the minor version before 1.0.0)"
+        }
+    );
+
+    for c in reasons {
+        eprintln!("{}", output.line(c)?);
+    }
+
+    Ok(())
+}
+
+/// Add the releases newer than the newest in a changelog file, and the unreleased changes, to its top.
+///
+/// Its Unreleased section is replaced, so running this again only brings it up to date.
+fn prepend_changelog(out: &mut dyn Write, path: &Path, query: &Query, file: &Path) -> anyhow::Result<()> {
+    //
+    let changelog = match std::fs::read_to_string(file) {
+        Ok(changelog) => changelog,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => "# Changelog\n".to_string(),
+        Err(e) => return Err(e).with_context(|| format!("Unable to read {}", file.display())),
+    };
+
+    let mut query = query.clone();
+
+    if let Some(version) = report::changelog_version(&changelog) {
+        let repo = git2::Repository::discover(path)?;
+        let Some(release) = tags::release(&repo, version)? else {
+            bail!("No tag found for {version}, the newest release in {}", file.display());
+        };
+
+        query.range = Some(format!("{}..HEAD", release.name));
+    }
+
+    let commits = Commit::query_all(&[path.to_path_buf()], &query)?;
+    let sections = report::changelog_sections(commits);
+
+    std::fs::write(file, report::prepend_changelog(&changelog, &sections))
+        .with_context(|| format!("Unable to write {}", file.display()))?;
+
+    writeln!(out, "Updated {}", file.display())?;
+
+    Ok(())
+}
+
+/// Print commits under the day they were made on, newest first, and their type within it, then a line of totals.
+fn print_report(out: &mut dyn Write, output: &Output<'_>, commits: Vec<Commit>) -> anyhow::Result<()> {
+    //
+    let tally = Tally::of(&commits, output.now());
+
+    for (day, commits) in report::sections(commits, GroupBy::Day, output.now()) {
+        writeln!(out, "{}", output.title(&day))?;
+        writeln!(out)?;
+
+        for (kind, commits) in report::sections(commits, GroupBy::Type, output.now()) {
+            writeln!(out, "{}", output.subtitle(&kind))?;
+            writeln!(out)?;
+
+            for line in output.lines(&commits)? {
+                writeln!(out, "{line}")?;
+            }
+
+            writeln!(out)?;
+        }
+    }
+
+    print_summary_line(out, output, &tally)?;
+
+    Ok(())
+}
+
+/// Manage the on-disk cache of forge lookups.
+fn run_cache(out: &mut dyn Write, action: &CacheAction) -> anyhow::Result<()> {
+    match action {
+        CacheAction::Clear => match cache::clear()? {
+            Some(dir) => writeln!(out, "Cleared {}", dir.display())?,
+            None => writeln!(out, "Nothing cached")?,
+        },
+    }
+
+    Ok(())
+}
+
+/// Print the most recent HEAD movements of each repository.
+fn print_reflog(out: &mut dyn Write, cli: &Cli, output: &Output<'_>, paths: &[PathBuf]) -> anyhow::Result<()> {
+    //
+    let count = match cli.count {
+        Some(0) => usize::MAX,
+        count => count.unwrap_or(5),
+    };
+
+    let mut movements = Vec::new();
+
+    for path in paths {
+        movements.push(reflog::movements(path, count, cli.remote.as_deref(), cli.abbrev())?);
+    }
+
+    if cli.template.is_some() || output.is_structured() {
+        let commits: Vec<_> = movements.into_iter().flatten().collect();
+
+        return print_structured(out, output, cli.format(), cli.template.as_deref(), &commits);
+    }
+
+    for commits in movements.iter().filter(|commits| !commits.is_empty()) {
+        print_heading(out, output, "Recent HEAD Movements")?;
+        print_section(out, output, None, &output.lines(commits)?)?;
+    }
+
+    Ok(())
+}
+
+/// Recent activity of every worktree of the given repositories, keyed by path and checked-out branch.
+fn worktree_activity(paths: &[PathBuf], query: &Query) -> anyhow::Result<Vec<(String, Vec<Commit>)>> {
+    //
+    let query = query.at_head();
+    let mut activity = Vec::new();
+
+    for path in paths {
+        for (worktree, branch) in commit::worktrees(path)? {
+            let title = format!("{} ({branch})", worktree.display());
+
+            match Commit::query(&worktree, &query) {
+                Err(e) if e.is::<UnbornHead>() => {}
+                commits => activity.push((title, commits?)),
+            }
+        }
+    }
+
+    Ok(activity)
+}
+
+/// Recent activity of every initialised submodule of the given repositories, keyed by submodule path.
+fn submodule_activity(paths: &[PathBuf], query: &Query) -> anyhow::Result<Vec<(String, Vec<Commit>)>> {
+    //
+    let query = query.at_head();
+    let mut activity = Vec::new();
+
+    for path in paths {
+        for (name, submodule) in commit::submodules(path)? {
+            match Commit::query(&submodule, &query) {
+                Err(e) if e.is::<UnbornHead>() => {}
+                commits => activity.push((name, commits?)),
+            }
+        }
+    }
+
+    Ok(activity)
+}
+
+/// Print the heading above the listing, if the output has one.
+fn print_heading(out: &mut dyn Write, output: &Output<'_>, heading: &str) -> io::Result<()> {
+    //
+    if let Some(heading) = output.heading(heading) {
+        writeln!(out, "{heading}")?;
+        writeln!(out)?;
+    }
+
+    Ok(())
+}
+
+/// Print the `--summary` line of what was listed, if anything was.
+fn print_summary_line(out: &mut dyn Write, output: &Output<'_>, tally: &Tally) -> io::Result<()> {
+    //
+    if let Some(summary) = tally.summary(output) {
+        writeln!(out, "{summary}")?;
+        writeln!(out)?;
+    }
+
+    Ok(())
+}
+
+/// Print a list of rendered commits, optionally under a section title.
+fn print_section(out: &mut dyn Write, output: &Output<'_>, title: Option<&str>, lines: &[String]) -> io::Result<()> {
+    //
+    if lines.is_empty() {
+        return Ok(());
+    }
+
+    if let Some(title) = title {
+        writeln!(out, "{}", output.title(title))?;
+        writeln!(out)?;
+    }
+
+    for line in lines {
+        writeln!(out, "{line}")?;
+    }
+
+    writeln!(out)
+}
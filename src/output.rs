@@ -0,0 +1,1382 @@
+use std::fmt::Write;
+
+use anyhow::Result;
+use colored::Colorize;
+use jiff::Zoned;
+use jiff::fmt::friendly::SpanPrinter;
+use serde::Serialize;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+use crate::commit::{Commit, Markup};
+use crate::{avatar, emoji, forge};
+
+/// Keep a Changelog sections in the order they are listed, with the conventional types filed under each.
+const CHANGELOG_SECTIONS: &[(&str, &[&str])] = &[
+    ("Added", &["feat"]),
+    ("Changed", &["perf", "refactor"]),
+    ("Deprecated", &["deprecate"]),
+    ("Removed", &["remove", "revert"]),
+    ("Fixed", &["fix"]),
+    ("Security", &["security"]),
+];
+
+const HTML_HEAD: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>Recent Activity</title>
+<style>
+  body { font-family: system-ui, sans-serif; max-width: 60rem; margin: 2rem auto; padding: 0 1rem; color: #24292f; }
+  ul { list-style: none; padding: 0; }
+  li { padding: 0.3rem 0; border-bottom: 1px solid #eaeef2; }
+  .hash { font-family: ui-monospace, monospace; color: #0969da; text-decoration: none; }
+  .repo { color: #8250df; }
+  .branches { color: #9a6700; }
+  .type { color: #0550ae; font-weight: 600; }
+  time { color: #57606a; font-size: 0.9em; }
+</style>
+</head>
+<body>
+<h1>Recent Activity</h1>
+<ul>
+"#;
+
+const HTML_TAIL: &str = r#"</ul>
+<script>
+  const units = [["year", 31536000], ["month", 2592000], ["week", 604800], ["day", 86400], ["hour", 3600], ["minute", 60]];
+  const format = new Intl.RelativeTimeFormat(undefined, { numeric: "auto" });
+
+  for (const time of document.querySelectorAll("time")) {
+    const seconds = (new Date(time.dateTime) - Date.now()) / 1000;
+    const [unit, size] = units.find(([, size]) => Math.abs(seconds) >= size) ?? ["second", 1];
+
+    time.title = time.textContent;
+    time.textContent = format.format(Math.round(seconds / size), unit);
+  }
+</script>
+</body>
+</html>
+"#;
+
+/// How recent activity is rendered.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum Format {
+    /// Colored, hyperlinked bullet points for the terminal.
+    #[default]
+    Text,
+    /// A JSON array of commit objects.
+    Json,
+    /// A bulleted list with `[hash](url)` links, for PR descriptions and release notes.
+    Markdown,
+    /// One JSON object per line, written as the history is walked.
+    Jsonl,
+    /// A standalone, styled HTML page.
+    Html,
+    /// Comma separated values, for spreadsheets.
+    Csv,
+    /// Tab separated values, for spreadsheets.
+    Tsv,
+    /// An Atom feed, for following a repository in a feed reader.
+    Atom,
+    /// A changelog in the Keep a Changelog layout, sectioned by release.
+    Keepachangelog,
+    /// A "What's Changed" body for `gh release create --notes-file -`.
+    GhRelease,
+    /// Slack mrkdwn, with `<url|hash>` links and emoji shortcodes.
+    Slack,
+    /// `hash: message` lines, or `file:1: message` with `--path`, for editor quickfix lists.
+    Quickfix,
+}
+
+impl Format {
+    /// Whether the format has a layout of its own, rather than headed sections of bullet points.
+    #[must_use]
+    pub fn is_structured(self) -> bool {
+        !matches!(self, Self::Text | Self::Markdown | Self::Slack)
+    }
+
+    /// Whether commits can be written one at a time as the history is walked.
+    #[must_use]
+    pub fn is_streamable(self) -> bool {
+        matches!(
+            self,
+            Self::Text | Self::Markdown | Self::Slack | Self::Jsonl | Self::Quickfix
+        )
+    }
+}
+
+/// Renders the headings and entries of a listing in a given format.
+#[allow(clippy::struct_excessive_bools)]
+pub struct Output<'a> {
+    format: Format,
+    pretty: Option<&'a str>,
+    plain: bool,
+    width: Option<usize>,
+    body: bool,
+    breaking_notes: bool,
+    strict: bool,
+    stat: bool,
+    stat_graph: bool,
+    null: bool,
+    urls: bool,
+    authors: bool,
+    avatars: bool,
+    now: &'a Zoned,
+    printer: &'a SpanPrinter,
+}
+
+/// A commit together with the values derived from it for display, as exposed to JSON and templates.
+#[derive(Serialize)]
+struct FormattedCommit<'a> {
+    #[serde(flatten)]
+    commit: &'a Commit,
+    url: Option<String>,
+    emoji: String,
+    title: String,
+    relative: String,
+}
+
+/// A row of spreadsheet output.
+#[derive(Serialize)]
+struct Row<'a> {
+    hash: String,
+    author: &'a str,
+    #[serde(rename = "type")]
+    kind: Option<&'a str>,
+    scope: Option<&'a str>,
+    breaking: bool,
+    description: &'a str,
+    timestamp: String,
+}
+
+impl<'a> Row<'a> {
+    fn new(c: &'a Commit) -> Self {
+        let cc = c.conventional.as_ref();
+
+        Row {
+            hash: c.oid.to_string(),
+            author: &c.author_name,
+            kind: cc.map(|cc| cc.kind.as_str()),
+            scope: cc.and_then(|cc| cc.scope.as_deref()),
+            breaking: c.is_breaking(),
+            description: subject(c),
+            timestamp: rfc3339(&c.timestamp),
+        }
+    }
+}
+
+impl<'a> Output<'a> {
+    pub fn new(format: Format, now: &'a Zoned, printer: &'a SpanPrinter) -> Self {
+        Self {
+            format,
+            pretty: None,
+            plain: false,
+            width: None,
+            body: false,
+            breaking_notes: false,
+            strict: false,
+            stat: false,
+            stat_graph: false,
+            null: false,
+            urls: false,
+            authors: false,
+            avatars: false,
+            now,
+            printer,
+        }
+    }
+
+    /// Render each commit with a `git log --pretty=format` style format instead.
+    #[must_use]
+    pub fn pretty(mut self, pretty: Option<&'a str>) -> Self {
+        self.pretty = pretty;
+        self
+    }
+
+    /// Leave out the heading, for output that is grepped rather than read.
+    #[must_use]
+    pub fn plain(mut self, plain: bool) -> Self {
+        self.plain = plain;
+        self
+    }
+
+    /// Truncate text lines to a number of terminal columns.
+    #[must_use]
+    pub fn width(mut self, width: Option<usize>) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Print each commit's body beneath its bullet point.
+    #[must_use]
+    pub fn body(mut self, body: bool) -> Self {
+        self.body = body;
+        self
+    }
+
+    /// Print the migration notes of each commit's `BREAKING CHANGE:` footer beneath its bullet point.
+    #[must_use]
+    pub fn breaking_notes(mut self, breaking_notes: bool) -> Self {
+        self.breaking_notes = breaking_notes;
+        self
+    }
+
+    /// Print why each commit that doesn't follow Conventional Commits fails to parse beneath its bullet point.
+    #[must_use]
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Write `hash\0formatted\0` records instead of lines, for scripts.
+    #[must_use]
+    pub fn null(mut self, null: bool) -> Self {
+        self.null = null;
+        self
+    }
+
+    /// Spell out each commit's URL after it, for terminals without hyperlinks.
+    #[must_use]
+    pub fn urls(mut self, urls: bool) -> Self {
+        self.urls = urls;
+        self
+    }
+
+    /// Credit each commit's author, linked to their forge profile when their login is known.
+    #[must_use]
+    pub fn authors(mut self, authors: bool) -> Self {
+        self.authors = authors;
+        self
+    }
+
+    /// Show each author's avatar, or their initials, beside the hash of their commits.
+    #[must_use]
+    pub fn avatars(mut self, avatars: bool) -> Self {
+        self.avatars = avatars;
+        self
+    }
+
+    /// Append diff stats, a diff stat bar, or both, to each commit.
+    #[must_use]
+    pub fn stats(mut self, stat: bool, graph: bool) -> Self {
+        self.stat = stat;
+        self.stat_graph = graph;
+        self
+    }
+
+    /// What ends each entry: a newline, or a NUL for `--null` records.
+    pub fn terminator(&self) -> &'static str {
+        if self.null { "\0" } else { "\n" }
+    }
+
+    /// The time ages are given relative to, and days are counted in the time zone of.
+    #[must_use]
+    pub fn now(&self) -> &Zoned {
+        self.now
+    }
+
+    /// Whether entries are written without headings or sections.
+    pub fn is_structured(&self) -> bool {
+        self.null || self.pretty.is_some() || self.format.is_structured()
+    }
+
+    /// Leading whitespace of every line, so terminal output stands apart from the prompt.
+    pub fn indent(&self) -> &'static str {
+        match self.format {
+            Format::Text => "  ",
+            _ => "",
+        }
+    }
+
+    /// The heading printed above the listing, which plain output leaves out.
+    pub fn heading(&self, heading: &str) -> Option<String> {
+        if self.plain {
+            return None;
+        }
+
+        Some(match self.format {
+            Format::Slack => format!("*{heading}*"),
+            _ => format!("{}## {heading}", self.indent()),
+        })
+    }
+
+    /// The title printed above a section of the listing.
+    pub fn title(&self, title: &str) -> String {
+        match self.format {
+            Format::Slack => format!("*{}*", slack_escape(title)),
+            _ => format!("{}### {title}", self.indent()),
+        }
+    }
+
+    /// The title printed above a section within a section, such as a type within a day of `devmoji-log report`.
+    pub fn subtitle(&self, title: &str) -> String {
+        match self.format {
+            Format::Slack => format!("_{}_", slack_escape(title)),
+            _ => format!("{}#### {title}", self.indent()),
+        }
+    }
+
+    /// The marker of a bullet point.
+    fn bullet(&self) -> &'static str {
+        match self.format {
+            Format::Slack => "•",
+            _ => "*",
+        }
+    }
+
+    /// Render a single commit as a bullet point, or a JSON object for JSON Lines.
+    pub fn line(&self, c: &Commit) -> Result<String> {
+        //
+        if self.format == Format::Jsonl {
+            return Ok(serde_json::to_string(&self.formatted(c)?)?);
+        }
+
+        if self.null {
+            return Ok(format!("{}\0{}", c.oid, c.format(self.now, self.printer)?));
+        }
+
+        if self.format == Format::Quickfix {
+            return self.quickfix(c);
+        }
+
+        if let Some(pretty) = self.pretty {
+            return Ok(self.fit(self.expand(pretty, c)?));
+        }
+
+        let mut line = if self.format == Format::Slack {
+            self.slack(c)?
+        } else {
+            let id = match (self.format, c.link()) {
+                (Format::Markdown, Some(link)) => format!("[{}]({link})", c.id),
+                (Format::Markdown, None) => c.id.clone(),
+                _ => c.id(),
+            };
+
+            let markup = match self.format {
+                Format::Markdown => Markup::Markdown,
+                _ => Markup::Terminal,
+            };
+
+            self.fit(format!(
+                "{}* {id} {}{}{}{}{}{}{}{}{}{}{}{}",
+                self.indent(),
+                self.avatar(c),
+                c.checks_mark(),
+                c.signature_mark(),
+                c.pull_request_mark(markup),
+                c.label(),
+                c.decoration(),
+                c.action_label(),
+                Self::nonconforming_mark(c),
+                c.autolink(&c.format(self.now, self.printer)?, markup),
+                self.author(c, markup),
+                Self::pull_request(c, markup),
+                self.diff_stats(c)
+            ))
+        };
+
+        if self.urls
+            && self.format == Format::Text
+            && let Some(link) = c.link()
+        {
+            let _ = write!(line, " <{link}>");
+        }
+
+        if self.body && !c.body().is_empty() {
+            line.push_str(&self.beneath(c.body(), true));
+        } else if self.breaking_notes
+            && let Some(note) = c.breaking_change()
+        {
+            line.push_str(&self.beneath(note, false));
+        }
+
+        if self.strict
+            && c.is_nonconforming()
+            && let Some(error) = c.parse_error()
+        {
+            line.push_str(&self.beneath(&error, true));
+        }
+
+        Ok(line)
+    }
+
+    /// A dim ⚠️ for commits that don't follow Conventional Commits, rather than showing them unformatted unremarked.
+    fn nonconforming_mark(c: &Commit) -> String {
+        //
+        if !c.is_nonconforming() {
+            return String::new();
+        }
+
+        format!("{} ", "⚠️".dimmed())
+    }
+
+    /// The author's avatar or initials for `--avatars`, which only text output has room for.
+    fn avatar(&self, c: &Commit) -> String {
+        //
+        if !self.avatars || self.format != Format::Text {
+            return String::new();
+        }
+
+        format!("{} ", avatar::avatar(c))
+    }
+
+    /// The author credited by `--show-author`, in the form of: " by Alice", linked to their profile.
+    fn author(&self, c: &Commit, markup: Markup) -> String {
+        //
+        if !self.authors {
+            return String::new();
+        }
+
+        match c.author_link() {
+            Some(link) => format!(" by {}", markup.link(&link, &c.author_name)),
+            None => format!(" by {}", c.author_name),
+        }
+    }
+
+    /// The pull request found by `--enrich`, in the form of: " · #42 Add a login page (merged)"
+    ///
+    /// GitLab merge requests are written "!42", followed by the status of their pipeline.
+    fn pull_request(c: &Commit, markup: Markup) -> String {
+        //
+        let (Some(pull), Some(url)) = (&c.pull, &c.url) else {
+            return String::new();
+        };
+
+        let number = forge::pull_request_reference(url, pull.number);
+        let number = match c.pull_request_link(pull.number) {
+            Some(link) => markup.link(&link, &number),
+            None => number,
+        };
+
+        let pipeline = pull
+            .pipeline
+            .map(|pipeline| format!(" {}", pipeline.emoji()))
+            .unwrap_or_default();
+
+        format!(" · {number} {} ({}){pipeline}", pull.title, pull.state)
+    }
+
+    /// The diff stats and graph requested for a commit, in the form of: " · 4 files, +120 −35 ++++++--"
+    fn diff_stats(&self, c: &Commit) -> String {
+        //
+        let stat = if self.stat { c.stat() } else { None };
+        let graph = if self.stat_graph { c.stat_graph() } else { None };
+
+        match (stat, graph) {
+            (Some(stat), Some(graph)) => format!(" · {stat} {graph}"),
+            (Some(part), None) | (None, Some(part)) => format!(" · {part}"),
+            (None, None) => String::new(),
+        }
+    }
+
+    /// Text such as a commit's body and footers, wrapped and indented beneath its bullet point.
+    fn beneath(&self, text: &str, dimmed: bool) -> String {
+        //
+        let indent = match self.format {
+            Format::Markdown => "  ",
+            _ => "    ",
+        };
+
+        let width = self.width.unwrap_or(80).saturating_sub(indent.len()).max(20);
+        let mut body = String::new();
+
+        for paragraph in text.lines() {
+            for line in wrap(paragraph, width) {
+                body.push('\n');
+
+                if dimmed && !line.is_empty() {
+                    let _ = write!(body, "{indent}{}", line.dimmed());
+                } else if !line.is_empty() {
+                    let _ = write!(body, "{indent}{line}");
+                }
+            }
+        }
+
+        // A blank line sets the next bullet point apart from the body.
+        body.push('\n');
+        body
+    }
+
+    /// Truncate a text line to the configured width.
+    fn fit(&self, line: String) -> String {
+        match self.width {
+            Some(width) if self.format == Format::Text => truncate(&line, width),
+            _ => line,
+        }
+    }
+
+    /// Render each commit as a line of its own.
+    pub fn lines(&self, commits: &[Commit]) -> Result<Vec<String>> {
+        commits.iter().map(|c| self.line(c)).collect()
+    }
+
+    /// Render a pseudo-entry for uncommitted changes in the working tree.
+    pub fn dirty(&self, files: usize) -> String {
+        //
+        let plural = if files == 1 { "" } else { "s" };
+
+        format!(
+            "{}{} {} {} {files} file{plural} changed (just now)",
+            self.indent(),
+            self.bullet(),
+            "(uncommitted)".cyan(),
+            match emoji::emoji_style() {
+                emoji::EmojiStyle::Badges => "[wip]".blue().to_string(),
+                _ => emoji::styled(emoji::commit_emoji("wip").unwrap_or_default()),
+            }
+        )
+    }
+
+    /// Render a commit as a compiler style message, once per changed file when paths were queried.
+    fn quickfix(&self, c: &Commit) -> Result<String> {
+        //
+        let message = c.format(self.now, self.printer)?;
+
+        if c.files.is_empty() {
+            return Ok(format!("{}: {message}", c.id));
+        }
+
+        Ok(c.files
+            .iter()
+            .map(|file| format!("{}:1: {} {message}", file.display(), c.id))
+            .collect::<Vec<_>>()
+            .join("\n"))
+    }
+
+    /// Render a single commit as a Slack mrkdwn bullet point, using shortcodes for its emoji.
+    fn slack(&self, c: &Commit) -> Result<String> {
+        //
+        let id = match c.link() {
+            Some(link) => format!("<{link}|{}>", c.id),
+            None => format!("`{}`", c.id),
+        };
+
+        let subject = match &c.conventional {
+            Some(cc) => {
+                let emoji = emoji::shortcodes(&c.emoji());
+
+                if emoji.is_empty() {
+                    format!("{}: {}", cc.header(), cc.description)
+                } else {
+                    format!("{}: {emoji} {}", cc.header(), cc.description)
+                }
+            }
+            None => subject(c).to_string(),
+        };
+
+        Ok(format!(
+            "• {id} {}{}{}{} ({} ago){}",
+            c.signature_mark(),
+            slack_escape(&c.label()),
+            slack_escape(&c.decoration()),
+            slack_escape(&subject),
+            c.age(self.now, self.printer)?,
+            self.diff_stats(c)
+        ))
+    }
+
+    /// Expand the `git log --pretty=format` style placeholders of a user supplied format for one commit.
+    fn expand(&self, format: &str, c: &Commit) -> Result<String> {
+        //
+        let cc = c.conventional.as_ref();
+        let mut line = String::new();
+        let mut rest = format;
+
+        while let Some(start) = rest.find('%') {
+            line.push_str(&rest[..start]);
+            rest = &rest[start + 1..];
+
+            let (expansion, consumed) = match rest.get(..2).unwrap_or(rest) {
+                "ar" => (format!("{} ago", c.age(self.now, self.printer)?), 2),
+                "ad" => (c.timestamp.strftime("%Y-%m-%d").to_string(), 2),
+                "an" => (c.author_name.clone(), 2),
+                "ae" => (c.author_email.clone(), 2),
+                two => match two.chars().next() {
+                    Some('h') => (c.id.clone(), 1),
+                    Some('H') => (c.oid.to_string(), 1),
+                    Some('e') => (c.emoji(), 1),
+                    Some('t') => (cc.map(|cc| cc.kind.clone()).unwrap_or_default(), 1),
+                    Some('S') => (cc.and_then(|cc| cc.scope.clone()).unwrap_or_default(), 1),
+                    Some('s') => (subject(c).to_string(), 1),
+                    Some('b') => (c.body().to_string(), 1),
+                    Some('n') => ("\n".to_string(), 1),
+                    Some('%') => ("%".to_string(), 1),
+                    // Unknown placeholders are left as they are, like git does.
+                    _ => ("%".to_string(), 0),
+                },
+            };
+
+            line.push_str(&expansion);
+            rest = &rest[consumed..];
+        }
+
+        line.push_str(rest);
+        Ok(line)
+    }
+
+    /// Render commits as a pretty printed JSON array.
+    pub fn json(&self, commits: &[Commit]) -> Result<String> {
+        //
+        let entries = commits
+            .iter()
+            .map(|commit| self.formatted(commit))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(serde_json::to_string_pretty(&entries)?)
+    }
+
+    /// Render commits as an Atom feed, newest first.
+    pub fn atom(&self, commits: &[Commit]) -> String {
+        //
+        let updated = commits.iter().map(|c| &c.timestamp).max().unwrap_or(self.now);
+        let home = commits.iter().find_map(|c| c.url.as_deref());
+
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+
+        xml.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+        xml.push_str("  <title>Recent Activity</title>\n");
+        xml.push_str("  <generator>devmoji-log</generator>\n");
+
+        match home {
+            Some(home) => {
+                let _ = writeln!(xml, "  <id>{}</id>", escape(home));
+                let _ = writeln!(xml, "  <link href=\"{}\"/>", escape(home));
+            }
+            None => xml.push_str("  <id>urn:devmoji-log:recent-activity</id>\n"),
+        }
+
+        let _ = writeln!(xml, "  <updated>{}</updated>", rfc3339(updated));
+
+        for c in commits {
+            xml.push_str("  <entry>\n");
+            let _ = writeln!(xml, "    <title>{}</title>", escape(&title(c)));
+
+            match c.link() {
+                Some(link) => {
+                    let _ = writeln!(xml, "    <id>{}</id>", escape(&link));
+                    let _ = writeln!(xml, "    <link href=\"{}\"/>", escape(&link));
+                }
+                None => {
+                    let _ = writeln!(xml, "    <id>urn:sha1:{}</id>", c.oid);
+                }
+            }
+
+            let _ = writeln!(xml, "    <updated>{}</updated>", rfc3339(&c.timestamp));
+            let _ = writeln!(
+                xml,
+                "    <author><name>{}</name><email>{}</email></author>",
+                escape(&c.author_name),
+                escape(&c.author_email)
+            );
+            let _ = writeln!(xml, "    <content type=\"text\">{}</content>", escape(c.message.trim()));
+            xml.push_str("  </entry>\n");
+        }
+
+        xml.push_str("</feed>\n");
+        xml
+    }
+
+    /// Render commits through a user supplied Tera template, which sees `commits` and `now`.
+    pub fn template(&self, template: &str, commits: &[Commit]) -> Result<String> {
+        //
+        let formatted = commits
+            .iter()
+            .map(|commit| self.formatted(commit))
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut context = tera::Context::new();
+
+        context.insert("commits", &formatted);
+        context.insert("now", &rfc3339(self.now));
+
+        Ok(tera::Tera::one_off(template, &context, false)?)
+    }
+
+    fn formatted<'c>(&self, commit: &'c Commit) -> Result<FormattedCommit<'c>> {
+        Ok(FormattedCommit {
+            commit,
+            url: commit.link(),
+            emoji: commit.emoji(),
+            title: title(commit),
+            relative: format!("{} ago", commit.age(self.now, self.printer)?),
+        })
+    }
+}
+
+/// Render commits as a standalone HTML page, with relative dates filled in by the browser.
+pub fn html(commits: &[Commit]) -> String {
+    //
+    let mut html = String::from(HTML_HEAD);
+
+    for c in commits {
+        let id = match c.link() {
+            Some(link) => format!(r#"<a class="hash" href="{}">{}</a>"#, escape(&link), c.id),
+            None => format!(r#"<span class="hash">{}</span>"#, c.id),
+        };
+
+        let _ = write!(html, "  <li>{id} {}", c.pull_request_mark(Markup::Html));
+
+        if let Some(repo) = &c.repo {
+            let _ = write!(html, r#"<span class="repo">[{}]</span> "#, escape(repo));
+        }
+
+        if !c.branches.is_empty() {
+            let _ = write!(
+                html,
+                r#"<span class="branches">({})</span> "#,
+                escape(&c.branches.join(", "))
+            );
+        }
+
+        match &c.conventional {
+            Some(cc) => {
+                let scope = cc.scope.as_ref().map(|s| format!("({s})")).unwrap_or_default();
+                let bang = if cc.breaking { "!" } else { "" };
+
+                let _ = write!(
+                    html,
+                    r#"<span class="type">{}{}{bang}:</span> {} {}"#,
+                    escape(&cc.kind),
+                    c.autolink(&escape(&scope), Markup::Html),
+                    c.emoji(),
+                    c.autolink(&escape(&cc.description), Markup::Html)
+                );
+            }
+            None => html.push_str(&c.autolink(&escape(c.message.lines().next().unwrap_or_default()), Markup::Html)),
+        }
+
+        let _ = writeln!(
+            html,
+            r#" <time datetime="{}">{}</time></li>"#,
+            c.timestamp.strftime("%Y-%m-%dT%H:%M:%S%:z"),
+            c.timestamp.strftime("%Y-%m-%d %H:%M")
+        );
+    }
+
+    html.push_str(HTML_TAIL);
+    html
+}
+
+/// Render commits as comma or tab separated values, with a header row.
+pub fn delimited(commits: &[Commit], delimiter: u8) -> Result<String> {
+    //
+    let mut writer = csv::WriterBuilder::new().delimiter(delimiter).from_writer(Vec::new());
+
+    for c in commits {
+        writer.serialize(Row::new(c))?;
+    }
+
+    Ok(String::from_utf8(writer.into_inner()?)?)
+}
+
+/// Render commits as a Keep a Changelog document, with a section per release and change type.
+pub fn keepachangelog(commits: &[Commit]) -> String {
+    //
+    let mut changelog = String::from("# Changelog\n");
+
+    let releases = sections(commits, |c| {
+        c.release.clone().unwrap_or_else(|| "Unreleased".to_string())
+    });
+
+    for (release, commits) in releases {
+        //
+        let mut entries = String::new();
+
+        for (section, kinds) in CHANGELOG_SECTIONS {
+            let lines = commits
+                .iter()
+                .filter(|c| {
+                    c.conventional
+                        .as_ref()
+                        .is_some_and(|cc| kinds.contains(&cc.canonical_kind()))
+                })
+                .map(|c| changelog_line(c))
+                .collect::<Vec<_>>();
+
+            if !lines.is_empty() {
+                let _ = write!(entries, "\n### {section}\n\n{}\n", lines.join("\n"));
+            }
+        }
+
+        if entries.is_empty() {
+            continue;
+        }
+
+        // A release is dated by its newest commit, which is the one it was tagged on.
+        match commits.iter().map(|c| &c.timestamp).max() {
+            Some(date) if release != "Unreleased" => {
+                let version = release.strip_prefix('v').unwrap_or(&release);
+                let _ = write!(changelog, "\n## [{version}] - {}\n", date.strftime("%Y-%m-%d"));
+            }
+            _ => {
+                let _ = write!(changelog, "\n## [{release}]\n");
+            }
+        }
+
+        changelog.push_str(&entries);
+    }
+
+    changelog
+}
+
+/// Render commits in the style of GitHub's generated release notes, crediting authors and pull requests.
+pub fn gh_release(commits: &[Commit]) -> String {
+    //
+    let mut notes = String::from("## What's Changed\n\n");
+
+    for c in commits {
+        let pull_request = c.pull_request();
+
+        // A merge's subject names the branch, so the pull request's title is taken from its body.
+        let mut title = match (c.is_merge(), pull_request) {
+            (true, Some(_)) => c
+                .message
+                .lines()
+                .skip(1)
+                .find(|line| !line.trim().is_empty())
+                .map_or_else(|| title(c), |line| line.trim().to_string()),
+            _ => title(c),
+        };
+
+        if let Some(number) = pull_request {
+            title = title.trim_end_matches(&format!(" (#{number})")).to_string();
+        }
+
+        let author = c
+            .login()
+            .map_or_else(|| c.author_name.clone(), |login| format!("@{login}"));
+
+        let link = match pull_request {
+            Some(number) => c.pull_request_link(number).unwrap_or_else(|| format!("#{number}")),
+            None => c.link().unwrap_or_else(|| c.id.clone()),
+        };
+
+        let _ = writeln!(notes, "* {title} by {author} in {link}");
+    }
+
+    notes
+}
+
+/// Render a commit as a Keep a Changelog entry.
+pub fn changelog_line(c: &Commit) -> String {
+    //
+    let mut entry = String::from("- ");
+
+    if c.is_breaking() {
+        entry.push_str("**BREAKING** ");
+    }
+
+    if let Some(scope) = c.conventional.as_ref().and_then(|cc| cc.scope.as_ref()) {
+        let _ = write!(entry, "**{}:** ", c.autolink(scope, Markup::Markdown));
+    }
+
+    let emoji = c.emoji();
+
+    if !emoji.is_empty() {
+        let _ = write!(entry, "{emoji} ");
+    }
+
+    let _ = write!(entry, "{}", c.autolink(subject(c), Markup::Markdown));
+
+    match c.link() {
+        Some(link) => {
+            let _ = write!(entry, " ([{}]({link}))", c.id);
+        }
+        None => {
+            let _ = write!(entry, " ({})", c.id);
+        }
+    }
+
+    entry
+}
+
+/// Bucket items by a section title, keeping sections in order of first appearance.
+pub fn sections<T>(items: impl IntoIterator<Item = T>, title: impl Fn(&T) -> String) -> Vec<(String, Vec<T>)> {
+    //
+    let mut sections: Vec<(String, Vec<T>)> = Vec::new();
+
+    for item in items {
+        let key = title(&item);
+
+        match sections.iter_mut().find(|(title, _)| *title == key) {
+            Some((_, bucket)) => bucket.push(item),
+            None => sections.push((key, vec![item])),
+        }
+    }
+
+    sections
+}
+
+/// The conventional description of a commit, or the first line of its message.
+fn subject(c: &Commit) -> &str {
+    c.conventional
+        .as_ref()
+        .map_or_else(|| c.message.lines().next().unwrap_or_default(), |cc| &cc.description)
+}
+
+/// The emoji-enhanced subject line of a commit, without colors or relative date.
+fn title(c: &Commit) -> String {
+    //
+    match &c.conventional {
+        Some(cc) => cc.title(),
+        None => c.message.lines().next().unwrap_or_default().to_string(),
+    }
+}
+
+/// Format a timestamp in RFC 3339 form, keeping its UTC offset.
+fn rfc3339(timestamp: &Zoned) -> String {
+    timestamp.strftime("%Y-%m-%dT%H:%M:%S%:z").to_string()
+}
+
+/// Greedily wrap a line at whitespace so each piece fits in `width` columns, where possible.
+fn wrap(line: &str, width: usize) -> Vec<String> {
+    //
+    let mut lines = vec![String::new()];
+
+    for word in line.split_whitespace() {
+        let current = lines.last_mut().expect("there is always a current line");
+
+        if current.is_empty() {
+            current.push_str(word);
+        } else if current.width() + 1 + word.width() <= width {
+            current.push(' ');
+            current.push_str(word);
+        } else {
+            lines.push(word.to_string());
+        }
+    }
+
+    lines
+}
+
+/// Truncate a line to a number of terminal columns with an ellipsis, counting emoji as double width.
+///
+/// ANSI colors and OSC-8 hyperlinks take up no columns, and are closed if the line is cut inside them. Lines that fit
+/// are left whole, escape sequences after their last character included.
+fn truncate(line: &str, width: usize) -> String {
+    //
+    let mut truncated = String::new();
+    let mut columns = 0;
+    let mut kept = 0;
+    let mut colored = false;
+    let mut in_hyperlink = false;
+    let mut cut = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        //
+        if c == '\x1B' {
+            let mut sequence = String::from(c);
+
+            match chars.next() {
+                // Operating system commands, such as hyperlinks, and the application and device control strings
+                // of images run up to a string terminator, or a bell.
+                Some(kind @ (']' | '_' | 'P')) => {
+                    sequence.push(kind);
+
+                    while let Some(c) = chars.next() {
+                        sequence.push(c);
+
+                        if c == '\x07' {
+                            break;
+                        }
+
+                        if c == '\x1B' && chars.peek() == Some(&'\\') {
+                            sequence.push(chars.next().unwrap_or_default());
+                            break;
+                        }
+                    }
+
+                    if !cut && sequence.starts_with("\x1B]8;;") {
+                        in_hyperlink = !sequence.starts_with("\x1B]8;;\x1B");
+                    }
+
+                    // Images take up the columns of an avatar; kitty's are sent in chunks, counted by the first.
+                    if sequence.starts_with("\x1B]1337;")
+                        || sequence.starts_with("\x1BP")
+                        || sequence.starts_with("\x1B_Gf")
+                    {
+                        columns += avatar::WIDTH;
+
+                        if !cut {
+                            kept += avatar::WIDTH;
+                        }
+                    }
+                }
+                // Control sequences, such as colors, run up to a final letter.
+                Some(next) => {
+                    sequence.push(next);
+                    colored |= !cut;
+
+                    for c in chars.by_ref() {
+                        sequence.push(c);
+
+                        if c.is_ascii_alphabetic() {
+                            break;
+                        }
+                    }
+                }
+                None => {}
+            }
+
+            if !cut {
+                truncated.push_str(&sequence);
+            }
+
+            continue;
+        }
+
+        // Measure whole grapheme clusters, since an emoji such as ♻️ is only double width with its variation selector.
+        let mut cluster = String::from(c);
+
+        while let Some(&next) = chars.peek() {
+            let mut extended = cluster.clone();
+            extended.push(next);
+
+            if extended.graphemes(true).nth(1).is_some() {
+                break;
+            }
+
+            cluster = extended;
+            chars.next();
+        }
+
+        let w = cluster.width();
+
+        columns += w;
+
+        // Keep what fits beside an ellipsis, in case the whole line turns out not to fit.
+        if !cut && kept + w < width {
+            kept += w;
+            truncated.push_str(&cluster);
+        } else {
+            cut = true;
+        }
+    }
+
+    if columns <= width {
+        return line.to_string();
+    }
+
+    truncated.push('…');
+
+    if in_hyperlink {
+        truncated.push_str("\x1B]8;;\x1B\\");
+    }
+
+    if colored {
+        truncated.push_str("\x1B[0m");
+    }
+
+    truncated
+}
+
+/// Escape the characters Slack reserves for links and mentions.
+fn slack_escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// Escape text for inclusion in HTML or XML.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn now() -> Zoned {
+        "2025-02-02T12:00:00[UTC]".parse().unwrap()
+    }
+
+    /// A commit in a GitHub repository, two days before `now()`.
+    fn commit(message: &str) -> Commit {
+        Commit {
+            url: Some("https://github.com/acme/widget".to_string()),
+            ..Commit::fixture(message, "2025-01-31T12:00:00[UTC]")
+        }
+    }
+
+    #[test]
+    fn truncate_keeps_lines_that_fit() {
+        assert_eq!(truncate("short", 10), "short");
+        assert_eq!(truncate("exactly10!", 10), "exactly10!");
+    }
+
+    #[test]
+    fn truncate_keeps_an_exact_fit_followed_by_a_reset() {
+        let line = "\x1B[36mexactly10!\x1B[0m";
+
+        assert_eq!(truncate(line, 10), line);
+    }
+
+    #[test]
+    fn truncate_leaves_room_for_the_ellipsis() {
+        assert_eq!(truncate("one character too long", 21), "one character too lo…");
+    }
+
+    #[test]
+    fn truncate_closes_colors_and_hyperlinks() {
+        let line = "\x1B]8;;https://example.com\x1B\\\x1B[36mlinked text\x1B[0m\x1B]8;;\x1B\\";
+
+        assert_eq!(
+            truncate(line, 5),
+            "\x1B]8;;https://example.com\x1B\\\x1B[36mlink…\x1B]8;;\x1B\\\x1B[0m"
+        );
+    }
+
+    #[test]
+    fn truncate_counts_emoji_as_double_width() {
+        assert_eq!(truncate("✨ sparkles", 11), "✨ sparkles");
+        assert_eq!(truncate("✨ sparkles", 10), "✨ sparkl…");
+    }
+
+    #[test]
+    fn truncate_counts_variation_selectors_as_double_width() {
+        let line = "refactor: ♻\u{fe0f} tidy up the thing";
+
+        assert_eq!(truncate(line, 30), line);
+        assert_eq!(truncate(line, 29), "refactor: ♻\u{fe0f} tidy up the thi…");
+        assert_eq!(truncate("⚙\u{fe0f} config", 3), "⚙\u{fe0f}…");
+    }
+
+    #[test]
+    fn json_adds_display_fields_to_each_commit() {
+        let (now, printer) = (now(), SpanPrinter::new());
+        let json = Output::new(Format::Json, &now, &printer)
+            .json(&[commit("feat(ui)!: add a button")])
+            .unwrap();
+        let entries: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(entries[0]["type"], "feat");
+        assert_eq!(entries[0]["scope"], "ui");
+        assert_eq!(entries[0]["breaking"], true);
+        assert_eq!(entries[0]["description"], "add a button");
+        assert_eq!(entries[0]["author_name"], "Alice");
+        assert_eq!(entries[0]["timestamp"], "2025-01-31T12:00:00+00:00");
+        assert_eq!(
+            entries[0]["url"],
+            format!("https://github.com/acme/widget/commit/{}", "0".repeat(40))
+        );
+        assert_eq!(entries[0]["relative"], "2d ago");
+    }
+
+    #[test]
+    fn markdown_links_the_hash_and_issues() {
+        let (now, printer) = (now(), SpanPrinter::new());
+        let line = Output::new(Format::Markdown, &now, &printer)
+            .line(&commit("fix: close the socket, closes #7"))
+            .unwrap();
+
+        assert!(line.starts_with(&format!(
+            "* [0000000](https://github.com/acme/widget/commit/{}) ",
+            "0".repeat(40)
+        )));
+        assert!(line.contains("[#7](https://github.com/acme/widget/issues/7)"));
+        assert!(line.ends_with(" (2d ago)"));
+    }
+
+    #[test]
+    fn jsonl_writes_each_commit_on_a_line_of_its_own() {
+        let (now, printer) = (now(), SpanPrinter::new());
+        let line = Output::new(Format::Jsonl, &now, &printer)
+            .line(&commit("feat: add a button\n\nWith a body."))
+            .unwrap();
+        let entry: serde_json::Value = serde_json::from_str(&line).unwrap();
+
+        assert!(!line.contains('\n'));
+        assert_eq!(entry["message"], "feat: add a button\n\nWith a body.");
+        assert_eq!(entry["relative"], "2d ago");
+    }
+
+    #[test]
+    fn html_escapes_messages() {
+        let c = commit("fix(ui): escape <script> & friends");
+        let page = html(std::slice::from_ref(&c));
+
+        assert!(page.starts_with("<!DOCTYPE html>"));
+        assert!(page.contains(&format!(
+            r#"<a class="hash" href="https://github.com/acme/widget/commit/{}">0000000</a>"#,
+            "0".repeat(40)
+        )));
+        assert!(page.contains(&format!(
+            r#"<span class="type">fix(ui):</span> {} escape &lt;script&gt; &amp; friends <time"#,
+            c.emoji()
+        )));
+        assert!(page.contains(r#"<time datetime="2025-01-31T12:00:00+00:00">2025-01-31 12:00</time></li>"#));
+    }
+
+    #[test]
+    fn delimited_writes_a_header_and_quotes_as_needed() {
+        let commits = [commit("feat(ui)!: add a button, and a link"), commit("Update README")];
+        let hash = "0".repeat(40);
+
+        assert_eq!(
+            delimited(&commits, b',').unwrap(),
+            format!(
+                "hash,author,type,scope,breaking,description,timestamp\n\
+                 {hash},Alice,feat,ui,true,\"add a button, and a link\",2025-01-31T12:00:00+00:00\n\
+                 {hash},Alice,,,false,Update README,2025-01-31T12:00:00+00:00\n"
+            )
+        );
+        assert_eq!(
+            delimited(&commits[1..], b'\t').unwrap(),
+            format!(
+                "hash\tauthor\ttype\tscope\tbreaking\tdescription\ttimestamp\n\
+                 {hash}\tAlice\t\t\tfalse\tUpdate README\t2025-01-31T12:00:00+00:00\n"
+            )
+        );
+    }
+
+    #[test]
+    fn atom_has_an_entry_per_commit() {
+        let (now, printer) = (now(), SpanPrinter::new());
+        let newer = Commit {
+            timestamp: "2025-02-01T08:30:00[UTC]".parse().unwrap(),
+            ..commit("fix: handle <empty> input")
+        };
+        let feed = Output::new(Format::Atom, &now, &printer).atom(&[newer, commit("feat: add a button")]);
+
+        assert!(
+            feed.starts_with(
+                "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<feed xmlns=\"http://www.w3.org/2005/Atom\">"
+            )
+        );
+        assert!(feed.contains("  <id>https://github.com/acme/widget</id>\n"));
+        assert!(feed.contains("  <updated>2025-02-01T08:30:00+00:00</updated>\n"));
+        assert_eq!(feed.matches("<entry>").count(), 2);
+        assert!(feed.contains("    <content type=\"text\">fix: handle &lt;empty&gt; input</content>\n"));
+        assert!(feed.contains("    <author><name>Alice</name><email>alice@example.com</email></author>\n"));
+        assert!(feed.ends_with("</feed>\n"));
+    }
+
+    #[test]
+    fn template_sees_commits_and_now() {
+        let (now, printer) = (now(), SpanPrinter::new());
+        let rendered = Output::new(Format::Text, &now, &printer)
+            .template(
+                "{{ now }}\n{% for c in commits %}{{ c.short_hash }} {{ c.type }} {{ c.relative }}\n{% endfor %}",
+                &[commit("feat(ui): add a button")],
+            )
+            .unwrap();
+
+        assert_eq!(rendered, "2025-02-02T12:00:00+00:00\n0000000 feat 2d ago\n");
+    }
+
+    #[test]
+    fn pretty_expands_git_log_placeholders() {
+        let (now, printer) = (now(), SpanPrinter::new());
+        let output = Output::new(Format::Text, &now, &printer).pretty(Some("%h %t(%S) %s%n%an <%ae> %ad, %ar %% %x"));
+
+        assert_eq!(
+            output.line(&commit("feat(ui): add a button")).unwrap(),
+            "0000000 feat(ui) add a button\nAlice <alice@example.com> 2025-01-31, 2d ago % %x"
+        );
+        assert_eq!(
+            output.line(&commit("Update README")).unwrap(),
+            "0000000 () Update README\nAlice <alice@example.com> 2025-01-31, 2d ago % %x"
+        );
+    }
+
+    #[test]
+    fn keepachangelog_sections_releases_by_change_type() {
+        let released = |message: &str, release: &str| Commit {
+            release: Some(release.to_string()),
+            url: None,
+            ..commit(message)
+        };
+        let commits = [
+            Commit {
+                url: None,
+                ..commit("fix: close the socket")
+            },
+            released("feat(ui)!: add a button", "v1.1.0"),
+            released("refactor: tidy up", "v1.1.0"),
+            released("chore: bump deps", "v1.1.0"),
+            released("chore: release", "v1.0.0"),
+        ];
+        let emoji = |i: usize| commits[i].emoji();
+
+        assert_eq!(
+            keepachangelog(&commits),
+            format!(
+                "# Changelog\n\
+                 \n## [Unreleased]\n\n### Fixed\n\n- {} close the socket (0000000)\n\
+                 \n## [1.1.0] - 2025-01-31\n\n### Added\n\n- **BREAKING** **ui:** {} add a button (0000000)\n\
+                 \n### Changed\n\n- {} tidy up (0000000)\n",
+                emoji(0),
+                emoji(1),
+                emoji(2)
+            )
+        );
+    }
+
+    #[test]
+    fn gh_release_credits_authors_and_pull_requests() {
+        let merge = Commit {
+            parent_count: 2,
+            author_email: "123+bob@users.noreply.github.com".to_string(),
+            ..commit("Merge pull request #34 from bob/topic\n\nAdd the thing")
+        };
+        let commits = [merge, commit("Update README (#35)"), commit("Tidy up")];
+
+        assert_eq!(
+            gh_release(&commits),
+            format!(
+                "## What's Changed\n\n\
+                 * Add the thing by @bob in https://github.com/acme/widget/pull/34\n\
+                 * Update README by Alice in https://github.com/acme/widget/pull/35\n\
+                 * Tidy up by Alice in https://github.com/acme/widget/commit/{}\n",
+                "0".repeat(40)
+            )
+        );
+    }
+
+    #[test]
+    fn slack_links_hashes_and_escapes_control_characters() {
+        let (now, printer) = (now(), SpanPrinter::new());
+        let c = commit("fix(parser): a < b && c");
+
+        assert_eq!(
+            Output::new(Format::Slack, &now, &printer).line(&c).unwrap(),
+            format!(
+                "• <https://github.com/acme/widget/commit/{}|0000000> fix(parser): {} a &lt; b &amp;&amp; c (2d ago)",
+                "0".repeat(40),
+                emoji::shortcodes(&c.emoji())
+            )
+        );
+    }
+
+    #[test]
+    fn null_prefixes_records_with_the_full_hash() {
+        let (now, printer) = (now(), SpanPrinter::new());
+        let output = Output::new(Format::Text, &now, &printer).null(true);
+        let line = output.line(&commit("fix: close the socket")).unwrap();
+
+        assert_eq!(output.terminator(), "\0");
+        assert!(line.starts_with(&format!("{}\0", "0".repeat(40))));
+        assert!(line.ends_with(" (2d ago)"));
+    }
+
+    #[test]
+    fn quickfix_writes_a_location_per_changed_file() {
+        let (now, printer) = (now(), SpanPrinter::new());
+        let output = Output::new(Format::Quickfix, &now, &printer);
+        let c = commit("fix: close the socket");
+        let message = c.format(&now, &printer).unwrap();
+        let touching = Commit {
+            files: vec!["src/a.rs".into(), "src/b.rs".into()],
+            ..commit("fix: close the socket")
+        };
+
+        assert_eq!(output.line(&c).unwrap(), format!("0000000: {message}"));
+        assert_eq!(
+            output.line(&touching).unwrap(),
+            format!("src/a.rs:1: 0000000 {message}\nsrc/b.rs:1: 0000000 {message}")
+        );
+    }
+}
@@ -0,0 +1,38 @@
+use std::path::Path;
+
+use anyhow::Result;
+
+use crate::commit::{self, Commit, DateSource};
+
+/// The most recent `count` movements of HEAD in the repository containing `path`, newest first, as the commits HEAD
+/// moved onto with the action that moved it, such as a checkout, reset, rebase or commit.
+pub fn movements(path: &Path, count: usize, remote: Option<&str>, abbrev: Option<usize>) -> Result<Vec<Commit>> {
+    //
+    let Ok(repo) = git2::Repository::discover(path) else {
+        return Ok(Vec::new());
+    };
+
+    let url = commit::remote_url(&repo, remote)?;
+    let reflog = repo.reflog("HEAD")?;
+    let mailmap = repo.mailmap().ok();
+
+    Ok(reflog
+        .iter()
+        .filter_map(|entry| {
+            let target = repo.find_commit(entry.id_new()).ok()?;
+            let message = entry.message().unwrap_or_default();
+
+            // Entries read "<action>: <details>", where commit details are the commit subject itself.
+            let (action, details) = message.split_once(": ").unwrap_or(("", message));
+
+            let mut commit = Commit::new(&target, url.as_deref(), mailmap.as_ref(), DateSource::Committer, abbrev);
+            commit.message = details.to_string();
+            commit.conventional = commit::Conventional::parse(details);
+            commit.timestamp = commit::zoned_from_time(&entry.committer().when());
+            commit.action = Some(action.to_string());
+
+            Some(commit)
+        })
+        .take(count)
+        .collect())
+}
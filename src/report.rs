@@ -0,0 +1,1218 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Write;
+
+use anyhow::Result;
+use colored::Colorize;
+use jiff::civil::Date;
+use jiff::tz::TimeZone;
+use jiff::{Span, Zoned};
+use semver::Version;
+use serde::Serialize;
+use unicode_width::UnicodeWidthStr;
+
+use crate::commit::Commit;
+use crate::output::{self, Output};
+use crate::{emoji, forge, tags};
+
+/// Section titles of conventional types, in the order their sections are listed.
+const TYPE_SECTIONS: &[(&str, &str)] = &[
+    ("feat", "Features"),
+    ("fix", "Fixes"),
+    ("perf", "Performance"),
+    ("refactor", "Refactors"),
+    ("revert", "Reverts"),
+    ("docs", "Documentation"),
+    ("test", "Tests"),
+    ("build", "Build"),
+    ("ci", "CI"),
+    ("style", "Style"),
+    ("chore", "Chores"),
+];
+
+/// Columns taken up by the longest bar of a `stats --chart`.
+const BAR_WIDTH: usize = 30;
+
+/// How `--group-by` sections the listing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum GroupBy {
+    /// Section commits by the version tag that first contains them.
+    Release,
+    /// Section commits by conventional type, features first.
+    Type,
+    /// Section commits by scope, such as the packages of a monorepo.
+    Scope,
+    /// Section commits by author, busiest first.
+    Author,
+    /// Section commits by the local day they were made on.
+    Day,
+    /// Section commits by the week, starting on Monday, they were made in.
+    Week,
+    /// Section commits by the month they were made in.
+    Month,
+}
+
+/// The start of the day six days before `now`, so that with today a report covers a week.
+pub fn week_start(now: &Zoned) -> Result<Zoned> {
+    Ok(now.start_of_day()?.checked_sub(Span::new().days(6))?)
+}
+
+/// Bucket commits into titled sections, listed in the order they are read in.
+///
+/// Days, weeks and months are those of `now`'s time zone, and titled relative to it.
+pub fn sections(commits: Vec<Commit>, group_by: GroupBy, now: &Zoned) -> Vec<(String, Vec<Commit>)> {
+    match group_by {
+        GroupBy::Release => by_release(commits),
+        GroupBy::Type => by_type(commits),
+        GroupBy::Scope => by_scope(commits),
+        GroupBy::Author => by_author(commits),
+        GroupBy::Day | GroupBy::Week | GroupBy::Month => {
+            output::sections(commits, |c| period_title(&c.timestamp, group_by, now))
+        }
+    }
+}
+
+/// Sections for each release, newest version first whatever order the walk met them in, after unreleased commits.
+fn by_release(commits: Vec<Commit>) -> Vec<(String, Vec<Commit>)> {
+    //
+    let mut sections = output::sections(commits, |c| {
+        c.release.clone().unwrap_or_else(|| "Unreleased".to_string())
+    });
+
+    sections.sort_by_key(|(release, _)| {
+        let version = tags::parse_version(release);
+
+        (version.is_some(), std::cmp::Reverse(version))
+    });
+
+    sections
+}
+
+/// Sections such as "✨ Features" and "🐛 Fixes", with unlisted types after them and other commits last.
+fn by_type(commits: Vec<Commit>) -> Vec<(String, Vec<Commit>)> {
+    //
+    let mut sections = output::sections(commits, |c| {
+        c.conventional
+            .as_ref()
+            .map(|cc| cc.canonical_kind().to_string())
+            .unwrap_or_default()
+    });
+
+    sections.sort_by_key(|(kind, _)| type_rank(kind));
+
+    sections
+        .into_iter()
+        .map(|(kind, commits)| (type_title(&kind), commits))
+        .collect()
+}
+
+/// Sections for each scope in alphabetical order, with unscoped commits in an "other" section last.
+fn by_scope(commits: Vec<Commit>) -> Vec<(String, Vec<Commit>)> {
+    //
+    let mut sections = output::sections(commits, |c| {
+        c.conventional
+            .as_ref()
+            .and_then(|cc| cc.scope.clone())
+            .unwrap_or_default()
+    });
+
+    sections.sort_by(|(a, _), (b, _)| (a.is_empty(), a).cmp(&(b.is_empty(), b)));
+
+    sections
+        .into_iter()
+        .map(|(scope, commits)| (scope_title(&scope), commits))
+        .collect()
+}
+
+/// The title of a scope's section, led by its emoji when it has one, or "other" for unscoped commits.
+fn scope_title(scope: &str) -> String {
+    //
+    if scope.is_empty() {
+        return "other".to_string();
+    }
+
+    match emoji::scope_emoji(scope).map(emoji::styled) {
+        Some(emoji) if !emoji.is_empty() => format!("{emoji} {scope}"),
+        _ => scope.to_string(),
+    }
+}
+
+/// Sections for each author, those with the most commits first and the rest alphabetically.
+fn by_author(commits: Vec<Commit>) -> Vec<(String, Vec<Commit>)> {
+    //
+    let mut sections = output::sections(commits, |c| c.author_name.clone());
+
+    sections.sort_by(|(a, a_commits), (b, b_commits)| b_commits.len().cmp(&a_commits.len()).then_with(|| a.cmp(b)));
+
+    sections
+}
+
+/// The title of the day, week or month a time falls in, locally, such as "Monday, 3 March" or "March 2025".
+///
+/// Days and weeks of other years than `now`'s are given their year too.
+fn period_title(time: &Zoned, group_by: GroupBy, now: &Zoned) -> String {
+    //
+    let date = time.with_time_zone(now.time_zone().clone()).date();
+
+    let (date, prefix) = match group_by {
+        GroupBy::Week => {
+            let monday = Span::new().days(date.weekday().to_monday_zero_offset());
+
+            (date.checked_sub(monday).unwrap_or(date), "Week of ")
+        }
+        GroupBy::Month => return date.strftime("%B %Y").to_string(),
+        _ => (date, ""),
+    };
+
+    if date.year() == now.year() {
+        format!("{prefix}{}", date.strftime("%A, %-d %B"))
+    } else {
+        format!("{prefix}{}", date.strftime("%A, %-d %B %Y"))
+    }
+}
+
+/// Whether a conventional type is one of the usual ones, such as `feat` or `chore`.
+#[must_use]
+pub fn is_known_type(kind: &str) -> bool {
+    TYPE_SECTIONS.iter().any(|(known, _)| *known == kind)
+}
+
+/// The usual conventional types, in the order their sections are listed.
+#[must_use]
+pub fn known_types() -> Vec<&'static str> {
+    TYPE_SECTIONS.iter().map(|(kind, _)| *kind).collect()
+}
+
+/// Where a type is listed among others: known types in the order of their sections, then unlisted ones, then none.
+fn type_rank(kind: &str) -> usize {
+    match TYPE_SECTIONS.iter().position(|(known, _)| *known == kind) {
+        Some(position) => position,
+        None if kind.is_empty() => TYPE_SECTIONS.len() + 1,
+        None => TYPE_SECTIONS.len(),
+    }
+}
+
+/// The title of a type's section, such as "✨ Features", or "Other" for commits without a type.
+fn type_title(kind: &str) -> String {
+    //
+    if kind.is_empty() {
+        return "Other".to_string();
+    }
+
+    let title = TYPE_SECTIONS
+        .iter()
+        .find(|(known, _)| *known == kind)
+        .map_or_else(|| capitalized(kind), |(_, title)| (*title).to_string());
+
+    match emoji::commit_emoji(kind).map(emoji::styled) {
+        Some(emoji) if !emoji.is_empty() => format!("{emoji} {title}"),
+        _ => title,
+    }
+}
+
+/// A word with its first letter in upper case.
+fn capitalized(word: &str) -> String {
+    //
+    let mut chars = word.chars();
+
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+/// A CHANGELOG.md with a section for each release, newest first, and one for each conventional type within it.
+///
+/// Merges and commits that aren't conventional are left out, as their changes are listed by the commits merged.
+pub fn changelog(commits: Vec<Commit>) -> String {
+    format!("# Changelog\n{}", changelog_sections(commits))
+}
+
+/// The release sections of a changelog, each led by a blank line.
+pub fn changelog_sections(commits: Vec<Commit>) -> String {
+    //
+    let mut changelog = String::new();
+
+    for (release, commits) in by_release(commits) {
+        // A release is dated by its newest commit, which is the one it was tagged on.
+        let date = commits.iter().map(|c| c.timestamp.clone()).max();
+
+        let commits: Vec<_> = commits
+            .into_iter()
+            .filter(|c| c.conventional.is_some() && !c.is_merge())
+            .collect();
+
+        if commits.is_empty() {
+            continue;
+        }
+
+        match date {
+            Some(date) if release != "Unreleased" => {
+                let _ = write!(changelog, "\n## {release} - {}\n", date.strftime("%Y-%m-%d"));
+            }
+            _ => {
+                let _ = write!(changelog, "\n## {release}\n");
+            }
+        }
+
+        changelog.push_str(&type_sections(commits));
+    }
+
+    changelog
+}
+
+/// Release notes for the commits between two revisions, by type, ending with a link comparing them on the forge.
+pub fn release_notes(commits: Vec<Commit>, from: &str, to: &str) -> String {
+    //
+    let compare = commits
+        .iter()
+        .find_map(|c| forge::compare_url(c.url.as_deref()?, from, to));
+
+    let commits: Vec<_> = commits
+        .into_iter()
+        .filter(|c| c.conventional.is_some() && !c.is_merge())
+        .collect();
+
+    let mut notes = type_sections(commits).trim_start().to_string();
+
+    if let Some(compare) = compare {
+        let _ = write!(notes, "\n**Full Changelog**: {compare}\n");
+    }
+
+    notes
+}
+
+/// A `###` section of changelog entries for each conventional type, each led by a blank line.
+fn type_sections(commits: Vec<Commit>) -> String {
+    //
+    let mut sections = String::new();
+
+    for (title, commits) in by_type(commits) {
+        let _ = write!(sections, "\n### {title}\n\n");
+
+        for c in &commits {
+            let _ = writeln!(sections, "{}", changelog_entry(c));
+        }
+    }
+
+    sections
+}
+
+/// The version of the newest release in an existing changelog, from its first `## ` heading other than Unreleased.
+///
+/// Both `## v1.2.0 - 2025-01-31` and Keep a Changelog's `## [1.2.0] - 2025-01-31` are understood.
+#[must_use]
+pub fn changelog_version(changelog: &str) -> Option<&str> {
+    changelog
+        .lines()
+        .filter_map(|line| line.strip_prefix("## "))
+        .filter_map(|heading| heading.trim_start_matches('[').split([']', ' ']).next())
+        .find(|version| !version.is_empty() && !version.eq_ignore_ascii_case("unreleased"))
+}
+
+/// An existing changelog with new sections inserted above its releases, in place of its Unreleased section.
+#[must_use]
+pub fn prepend_changelog(changelog: &str, sections: &str) -> String {
+    //
+    let mut preamble = String::new();
+    let mut rest = String::new();
+    let mut heading = None;
+
+    for line in changelog.lines() {
+        if let Some(title) = line.strip_prefix("## ")
+            && heading.is_none_or(|unreleased| unreleased)
+        {
+            heading = Some(title.trim_start_matches('[').to_lowercase().starts_with("unreleased"));
+        }
+
+        let target = match heading {
+            None => &mut preamble,
+            Some(true) => continue,
+            Some(false) => &mut rest,
+        };
+
+        target.push_str(line);
+        target.push('\n');
+    }
+
+    let mut prepended = preamble.trim_end().to_string();
+
+    for part in [sections.trim(), rest.trim()] {
+        if !part.is_empty() {
+            let _ = write!(prepended, "\n\n{part}");
+        }
+    }
+
+    format!("{}\n", prepended.trim_start())
+}
+
+/// A changelog entry linking the commit, and the pull request it was merged through unless its description names it.
+fn changelog_entry(c: &Commit) -> String {
+    //
+    let mut entry = output::changelog_line(c);
+
+    if let Some(number) = c.pull_request()
+        && let Some(url) = &c.url
+        && let Some(link) = c.pull_request_link(number)
+    {
+        let reference = forge::pull_request_reference(url, number);
+
+        if !c
+            .conventional
+            .as_ref()
+            .is_some_and(|cc| cc.description.contains(&reference))
+        {
+            let _ = write!(entry, " in [{reference}]({link})");
+        }
+    }
+
+    entry
+}
+
+/// The semantic version bump commits call for, from least to most.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Bump {
+    Patch,
+    Minor,
+    Major,
+}
+
+impl Bump {
+    /// The bump a commit calls for: major when breaking, minor for a feature, and otherwise a patch.
+    #[must_use]
+    pub fn of(c: &Commit) -> Bump {
+        match &c.conventional {
+            Some(cc) if cc.breaking => Bump::Major,
+            Some(cc) if cc.canonical_kind() == "feat" => Bump::Minor,
+            _ => Bump::Patch,
+        }
+    }
+
+    /// The bump's name, as CI scripts expect it.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Bump::Patch => "patch",
+            Bump::Minor => "minor",
+            Bump::Major => "major",
+        }
+    }
+
+    /// The bump as made to `version`: breaking changes before 1.0.0 only bump the minor version.
+    #[must_use]
+    pub fn at(self, version: &Version) -> Bump {
+        match self {
+            Bump::Major if version.major == 0 => Bump::Minor,
+            bump => bump,
+        }
+    }
+
+    /// The version after `version` with this bump, as made to it by `at`.
+    ///
+    /// A pre-release such as 1.3.0-rc.1 already stands for the release it precedes, so it becomes 1.3.0 unless the
+    /// bump goes beyond that release, as a minor bump of 1.3.1-rc.1 does to 1.4.0.
+    #[must_use]
+    pub fn apply(self, version: &Version) -> Version {
+        //
+        let (major, minor, patch) = (version.major, version.minor, version.patch);
+        let released = version.pre.is_empty();
+
+        match self.at(version) {
+            Bump::Major if !released && minor == 0 && patch == 0 => Version::new(major, 0, 0),
+            Bump::Major => Version::new(major + 1, 0, 0),
+            Bump::Minor if !released && patch == 0 => Version::new(major, minor, 0),
+            Bump::Minor => Version::new(major, minor + 1, 0),
+            Bump::Patch if !released => Version::new(major, minor, patch),
+            Bump::Patch => Version::new(major, minor, patch + 1),
+        }
+    }
+}
+
+/// Running totals of the commits listed, for the `--summary` line after them.
+#[derive(Debug)]
+pub struct Tally {
+    time_zone: TimeZone,
+    commits: usize,
+    authors: HashSet<String>,
+    days: HashSet<Date>,
+    types: HashMap<String, usize>,
+    breaking: usize,
+}
+
+impl Tally {
+    /// Empty totals, counting days in `now`'s time zone as grouping by day does.
+    #[must_use]
+    pub fn new(now: &Zoned) -> Tally {
+        Tally {
+            time_zone: now.time_zone().clone(),
+            commits: 0,
+            authors: HashSet::new(),
+            days: HashSet::new(),
+            types: HashMap::new(),
+            breaking: 0,
+        }
+    }
+
+    /// Totals of the given commits.
+    #[must_use]
+    pub fn of<'a>(commits: impl IntoIterator<Item = &'a Commit>, now: &Zoned) -> Tally {
+        //
+        let mut tally = Tally::new(now);
+
+        for c in commits {
+            tally.add(c);
+        }
+
+        tally
+    }
+
+    /// Count a listed commit.
+    pub fn add(&mut self, c: &Commit) {
+        //
+        self.commits += 1;
+        self.authors.insert(c.author_name.clone());
+        self.days
+            .insert(c.timestamp.with_time_zone(self.time_zone.clone()).date());
+
+        if let Some(cc) = &c.conventional {
+            *self.types.entry(cc.canonical_kind().to_string()).or_default() += 1;
+            self.breaking += usize::from(cc.breaking);
+        }
+    }
+
+    /// A line such as "— 14 commits by 3 authors over 6 days (4 feat, 7 fix, 1 breaking 💥)", if any were counted.
+    #[must_use]
+    pub fn summary(&self, output: &Output<'_>) -> Option<String> {
+        //
+        if self.commits == 0 {
+            return None;
+        }
+
+        let mut types: Vec<_> = self.types.iter().collect();
+
+        types.sort_by_key(|(kind, _)| (type_rank(kind), *kind));
+
+        let mut counts: Vec<_> = types.iter().map(|(kind, count)| format!("{count} {kind}")).collect();
+
+        if self.breaking > 0 {
+            let emoji = emoji::commit_emoji("breaking").map(emoji::styled).unwrap_or_default();
+
+            counts.push(format!("{} breaking {emoji}", self.breaking).trim_end().to_string());
+        }
+
+        let counts = if counts.is_empty() {
+            String::new()
+        } else {
+            format!(" ({})", counts.join(", "))
+        };
+
+        Some(format!(
+            "{}— {} by {} over {}{counts}",
+            output.indent(),
+            plural(self.commits, "commit"),
+            plural(self.authors.len(), "author"),
+            plural(self.days.len(), "day"),
+        ))
+    }
+}
+
+/// A name and how many commits it accounts for, in a `stats` table.
+#[derive(Debug, Serialize)]
+pub struct Count {
+    pub name: String,
+    pub commits: usize,
+}
+
+/// Totals over the selected commits, for `devmoji-log stats`.
+#[derive(Debug, Serialize)]
+pub struct Stats {
+    pub commits: usize,
+    pub insertions: usize,
+    pub deletions: usize,
+    pub active_days: usize,
+    pub authors: Vec<Count>,
+    pub types: Vec<Count>,
+    pub scopes: Vec<Count>,
+    pub busiest_day: Option<Count>,
+}
+
+impl Stats {
+    /// Count commits by author, type, scope and local day.
+    #[must_use]
+    pub fn new(commits: &[Commit], now: &Zoned) -> Self {
+        //
+        let days = counts(commits.iter().map(|c| {
+            c.timestamp
+                .with_time_zone(now.time_zone().clone())
+                .date()
+                .strftime("%A, %-d %B %Y")
+                .to_string()
+        }));
+
+        Stats {
+            commits: commits.len(),
+            insertions: commits
+                .iter()
+                .filter_map(|c| c.stats)
+                .map(|stats| stats.insertions)
+                .sum(),
+            deletions: commits
+                .iter()
+                .filter_map(|c| c.stats)
+                .map(|stats| stats.deletions)
+                .sum(),
+            active_days: days.len(),
+            authors: counts(commits.iter().map(|c| c.author_name.clone())),
+            types: counts(
+                commits
+                    .iter()
+                    .filter_map(|c| c.conventional.as_ref().map(|cc| cc.canonical_kind().to_string())),
+            ),
+            scopes: counts(commits.iter().filter_map(|c| c.conventional.as_ref()?.scope.clone())),
+            busiest_day: days.into_iter().next(),
+        }
+    }
+
+    /// Aligned tables of the counts, under a title for each, with a bar chart of them when `chart` is set.
+    #[must_use]
+    pub fn render(&self, output: &Output<'_>, chart: bool) -> String {
+        //
+        let indent = output.indent();
+
+        let mut rendered = format!(
+            "{indent}{} · {} · {} · {} {}\n",
+            plural(self.commits, "commit"),
+            plural(self.authors.len(), "author"),
+            plural(self.active_days, "active day"),
+            format!("+{}", self.insertions).green(),
+            format!("−{}", self.deletions).red(),
+        );
+
+        // The leaderboard's top three get medals.
+        let authors = self.authors.iter().enumerate().map(|(rank, author)| {
+            let medal = ["🥇", "🥈", "🥉"].get(rank).copied().unwrap_or_default();
+
+            (medal.to_string(), author)
+        });
+
+        let types = self.types.iter().map(|kind| {
+            let emoji = emoji::commit_emoji(&kind.name).map(emoji::styled).unwrap_or_default();
+
+            (emoji, kind)
+        });
+
+        let scopes = self.scopes.iter().map(|scope| {
+            let emoji = emoji::scope_emoji(&scope.name).map(emoji::styled).unwrap_or_default();
+
+            (emoji, scope)
+        });
+
+        for (title, rows) in [
+            ("👥 Authors", authors.collect::<Vec<_>>()),
+            ("🏷️ Types", types.collect()),
+            ("📦 Scopes", scopes.collect()),
+        ] {
+            if !rows.is_empty() {
+                let _ = write!(
+                    rendered,
+                    "\n{}\n\n{}",
+                    output.title(title),
+                    table(indent, &rows, self.commits, chart)
+                );
+            }
+        }
+
+        if let Some(day) = &self.busiest_day {
+            let _ = write!(
+                rendered,
+                "\n{}\n\n{indent}{} ({})\n",
+                output.title("📅 Busiest day"),
+                day.name,
+                plural(day.commits, "commit")
+            );
+        }
+
+        rendered
+    }
+}
+
+/// How many times each name occurs, most frequent first and then alphabetically.
+fn counts(names: impl Iterator<Item = String>) -> Vec<Count> {
+    //
+    let mut counts: Vec<Count> = output::sections(names, Clone::clone)
+        .into_iter()
+        .map(|(name, occurrences)| Count {
+            name,
+            commits: occurrences.len(),
+        })
+        .collect();
+
+    counts.sort_by(|a, b| b.commits.cmp(&a.commits).then_with(|| a.name.cmp(&b.name)));
+    counts
+}
+
+/// Rows of an emoji and a name, with the commit count and its share of `total`, in aligned columns.
+///
+/// With `chart`, each row ends in a bar as long as its count relative to the largest.
+fn table(indent: &str, rows: &[(String, &Count)], total: usize, chart: bool) -> String {
+    //
+    let most = |column: fn(&(String, &Count)) -> usize| rows.iter().map(column).max().unwrap_or_default();
+    let emoji_width = most(|(emoji, _)| emoji.width());
+    let name_width = most(|(_, count)| count.name.width());
+    let count_width = most(|(_, count)| count.commits.to_string().len());
+    let largest = most(|(_, count)| count.commits);
+
+    rows.iter().fold(String::new(), |mut table, (emoji, count)| {
+        let padded = |text: &str, width: usize| format!("{text}{}", " ".repeat(width - text.width()));
+        let emoji = if emoji_width > 0 {
+            padded(emoji, emoji_width + 1)
+        } else {
+            String::new()
+        };
+        let share = count.commits * 100 / total.max(1);
+        let bar = if chart {
+            format!("  {}", bar(count.commits, largest))
+        } else {
+            String::new()
+        };
+
+        let _ = writeln!(
+            table,
+            "{indent}{emoji}{}  {:>count_width$}  {share:>3}%{bar}",
+            padded(&count.name, name_width),
+            count.commits
+        );
+        table
+    })
+}
+
+/// A horizontal bar of up to `BAR_WIDTH` columns for `count` out of `largest`, drawn to an eighth of a column.
+fn bar(count: usize, largest: usize) -> String {
+    //
+    const EIGHTHS: [&str; 8] = ["", "▏", "▎", "▍", "▌", "▋", "▊", "▉"];
+
+    let eighths = (count * BAR_WIDTH * 8 / largest.max(1)).max(usize::from(count > 0));
+
+    format!("{}{}", "█".repeat(eighths / 8), EIGHTHS[eighths % 8])
+}
+
+/// Commits per day over the weeks of `devmoji-log heatmap`, from the Monday starting the first to today.
+#[derive(Debug, Serialize)]
+pub struct Heatmap {
+    pub weeks: u16,
+    pub commits: usize,
+    pub days: Vec<Count>,
+    #[serde(skip)]
+    start: Date,
+}
+
+impl Heatmap {
+    /// Characters of each level of activity, from none to the busiest days, readable without colors too.
+    const LEVELS: [&str; 5] = ["·", "░", "▒", "▓", "█"];
+
+    /// The start of the Monday beginning the first of the last `weeks` weeks, locally.
+    pub fn start(weeks: u16, now: &Zoned) -> Result<Zoned> {
+        //
+        let days = i64::from(now.date().weekday().to_monday_zero_offset()) + 7 * (i64::from(weeks) - 1);
+
+        Ok(now.start_of_day()?.checked_sub(Span::new().days(days))?)
+    }
+
+    /// Count commits by the local day they were made on.
+    pub fn new(commits: &[Commit], weeks: u16, now: &Zoned) -> Result<Self> {
+        //
+        let start = Self::start(weeks, now)?.date();
+        let mut counts = BTreeMap::new();
+
+        for c in commits {
+            *counts
+                .entry(c.timestamp.with_time_zone(now.time_zone().clone()).date())
+                .or_insert(0) += 1;
+        }
+
+        let days: Vec<_> = start
+            .series(Span::new().days(1))
+            .take_while(|date| *date <= now.date())
+            .map(|date| Count {
+                name: date.to_string(),
+                commits: counts.get(&date).copied().unwrap_or_default(),
+            })
+            .collect();
+
+        Ok(Heatmap {
+            weeks,
+            commits: days.iter().map(|day| day.commits).sum(),
+            days,
+            start,
+        })
+    }
+
+    /// A grid of days with a column for each week and a row for each weekday, under the months they begin.
+    #[must_use]
+    pub fn render(&self, output: &Output<'_>) -> String {
+        //
+        let indent = output.indent();
+        let busiest = self.days.iter().map(|day| day.commits).max().unwrap_or_default();
+
+        let paint = |level: usize| {
+            if level == 0 {
+                Heatmap::LEVELS[0].dimmed().to_string()
+            } else {
+                Heatmap::LEVELS[level].green().to_string()
+            }
+        };
+
+        // Days are drawn by quarters of the busiest day, as GitHub does.
+        let cell = |commits: usize| {
+            paint(if commits == 0 {
+                0
+            } else {
+                (commits * 4).div_ceil(busiest).clamp(1, 4)
+            })
+        };
+
+        let mut rendered = format!("{indent}    {}\n", self.months());
+
+        for (weekday, label) in ["Mon", "", "Wed", "", "Fri", "", "Sun"].iter().enumerate() {
+            let row: Vec<_> = self
+                .days
+                .iter()
+                .skip(weekday)
+                .step_by(7)
+                .map(|day| cell(day.commits))
+                .collect();
+
+            let _ = writeln!(
+                rendered,
+                "{}",
+                format!("{indent}{label:<4}{}", row.join(" ")).trim_end()
+            );
+        }
+
+        let legend: Vec<_> = (0..Heatmap::LEVELS.len()).map(paint).collect();
+
+        let _ = writeln!(
+            rendered,
+            "\n{indent}{} in the last {}    Less {} More",
+            plural(self.commits, "commit"),
+            plural(self.weeks.into(), "week"),
+            legend.join(" ")
+        );
+
+        rendered
+    }
+
+    /// Names of months above the first week starting in each, leaving out any too close to the next to fit.
+    fn months(&self) -> String {
+        //
+        let mut labels = Vec::new();
+        let mut previous = None;
+
+        for (week, monday) in self
+            .start
+            .series(Span::new().weeks(1))
+            .take(self.weeks.into())
+            .enumerate()
+        {
+            if previous != Some(monday.month()) {
+                labels.push((week * 2, monday.strftime("%b").to_string()));
+            }
+
+            previous = Some(monday.month());
+        }
+
+        // Later months win, so a partly shown first month gives way to the next.
+        let mut next = usize::MAX;
+
+        labels.reverse();
+        labels.retain(|(column, label)| {
+            let fits = column + label.len() < next;
+
+            if fits {
+                next = *column;
+            }
+
+            fits
+        });
+
+        labels.iter().rev().fold(String::new(), |mut months, (column, label)| {
+            let _ = write!(months, "{:width$}{label}", "", width = column - months.len());
+            months
+        })
+    }
+}
+
+/// A count of a noun, such as "1 commit" or "3 commits".
+fn plural(count: usize, noun: &str) -> String {
+    format!("{count} {noun}{}", if count == 1 { "" } else { "s" })
+}
+
+#[cfg(test)]
+mod tests {
+    use jiff::fmt::friendly::SpanPrinter;
+
+    use super::*;
+    use crate::output::Format;
+
+    fn titles(sections: &[(String, Vec<Commit>)]) -> Vec<&str> {
+        sections.iter().map(|(title, _)| title.as_str()).collect()
+    }
+
+    fn commit(message: &str) -> Commit {
+        Commit::fixture(message, "2025-01-31T12:00:00[UTC]")
+    }
+
+    fn now() -> Zoned {
+        "2025-02-05T12:00:00[UTC]".parse().unwrap()
+    }
+
+    #[test]
+    fn sections_by_day_in_the_time_zone_of_now() {
+        let now: Zoned = "2025-02-05T12:00:00[America/New_York]".parse().unwrap();
+        let commits = vec![
+            Commit::fixture("feat: late", "2025-02-04T03:00:00[UTC]"),
+            Commit::fixture("fix: early", "2025-02-03T15:00:00[UTC]"),
+        ];
+
+        // 03:00 UTC is still the evening before in New York.
+        assert_eq!(titles(&sections(commits, GroupBy::Day, &now)), ["Monday, 3 February"]);
+    }
+
+    #[test]
+    fn tally_counts_days_in_the_time_zone_of_now() {
+        let commits = [
+            Commit::fixture("feat: late", "2025-02-04T03:00:00[UTC]"),
+            Commit::fixture("fix: early", "2025-02-03T15:00:00[UTC]"),
+        ];
+
+        let utc: Zoned = "2025-02-05T12:00:00[UTC]".parse().unwrap();
+        let new_york = utc.with_time_zone(TimeZone::get("America/New_York").unwrap());
+
+        assert_eq!(Tally::of(&commits, &utc).days.len(), 2);
+        assert_eq!(Tally::of(&commits, &new_york).days.len(), 1);
+    }
+
+    const CHANGELOG: &str =
+        "# Changelog\n\nAll notable changes.\n\n## [Unreleased]\n\n- old entry\n\n## v1.0.0 - 2025-01-01\n\n- first\n";
+
+    #[test]
+    fn prepend_changelog_replaces_the_unreleased_section() {
+        assert_eq!(
+            prepend_changelog(CHANGELOG, "## Unreleased\n\n- new entry\n"),
+            "# Changelog\n\nAll notable changes.\n\n## Unreleased\n\n- new entry\n\n## v1.0.0 - 2025-01-01\n\n- first\n"
+        );
+    }
+
+    #[test]
+    fn prepend_changelog_is_idempotent() {
+        let sections = "## Unreleased\n\n- new entry\n";
+        let once = prepend_changelog(CHANGELOG, sections);
+
+        assert_eq!(prepend_changelog(&once, sections), once);
+    }
+
+    #[test]
+    fn prepend_changelog_to_an_empty_file() {
+        assert_eq!(
+            prepend_changelog("", "## v1.1.0\n\n- entry\n"),
+            "## v1.1.0\n\n- entry\n"
+        );
+    }
+
+    #[test]
+    fn changelog_version_skips_unreleased() {
+        assert_eq!(changelog_version(CHANGELOG), Some("v1.0.0"));
+        assert_eq!(changelog_version("## [v2.0.0] - 2025-01-01\n"), Some("v2.0.0"));
+        assert_eq!(changelog_version("# Changelog\n"), None);
+    }
+
+    #[test]
+    fn bump_apply() {
+        let version = Version::new(1, 2, 3);
+
+        assert_eq!(Bump::Patch.apply(&version), Version::new(1, 2, 4));
+        assert_eq!(Bump::Minor.apply(&version), Version::new(1, 3, 0));
+        assert_eq!(Bump::Major.apply(&version), Version::new(2, 0, 0));
+    }
+
+    #[test]
+    fn bump_apply_keeps_breaking_changes_below_one() {
+        let version = Version::new(0, 4, 1);
+
+        assert_eq!(Bump::Major.at(&version), Bump::Minor);
+        assert_eq!(Bump::Major.apply(&version), Version::new(0, 5, 0));
+        assert_eq!(Bump::Minor.apply(&version), Version::new(0, 5, 0));
+        assert_eq!(Bump::Patch.apply(&version), Version::new(0, 4, 2));
+        assert_eq!(Bump::Major.at(&Version::new(1, 0, 0)), Bump::Major);
+    }
+
+    #[test]
+    fn bump_apply_releases_pre_releases() {
+        let rc = Version::parse("1.3.0-rc.1").unwrap();
+
+        assert_eq!(Bump::Patch.apply(&rc), Version::new(1, 3, 0));
+        assert_eq!(Bump::Minor.apply(&rc), Version::new(1, 3, 0));
+        assert_eq!(Bump::Major.apply(&rc), Version::new(2, 0, 0));
+
+        let rc = Version::parse("1.3.1-rc.1").unwrap();
+
+        assert_eq!(Bump::Patch.apply(&rc), Version::new(1, 3, 1));
+        assert_eq!(Bump::Minor.apply(&rc), Version::new(1, 4, 0));
+        assert_eq!(
+            Bump::Major.apply(&Version::parse("2.0.0-beta.2").unwrap()),
+            Version::new(2, 0, 0)
+        );
+    }
+
+    #[test]
+    fn sections_by_release_put_unreleased_then_newest_versions_first() {
+        let released = |message: &str, release: &str| Commit {
+            release: Some(release.to_string()),
+            ..commit(message)
+        };
+        let commits = vec![
+            released("fix: two", "v1.2.0"),
+            commit("feat: unreleased"),
+            released("feat: ten", "v1.10.0"),
+            released("fix: more two", "v1.2.0"),
+            released("chore: one", "v1.0.0"),
+        ];
+        let sections = sections(commits, GroupBy::Release, &now());
+
+        assert_eq!(titles(&sections), ["Unreleased", "v1.10.0", "v1.2.0", "v1.0.0"]);
+        assert_eq!(sections[2].1.len(), 2);
+    }
+
+    #[test]
+    fn sections_by_type_follow_the_usual_order() {
+        let commits = vec![
+            commit("chore: bump deps"),
+            commit("Update README"),
+            commit("wip: half done"),
+            commit("fix: close the socket"),
+            commit("feat(ui): add a button"),
+            commit("fix: handle nulls"),
+        ];
+        let sections = sections(commits, GroupBy::Type, &now());
+        let titles = titles(&sections);
+
+        assert_eq!(titles.len(), 5);
+        assert!(titles[0].ends_with("Features"));
+        assert!(titles[1].ends_with("Fixes"));
+        assert!(titles[2].ends_with("Chores"));
+        assert!(titles[3].ends_with("Wip"));
+        assert_eq!(titles[4], "Other");
+        assert_eq!(sections[1].1.len(), 2);
+    }
+
+    #[test]
+    fn sections_by_scope_are_alphabetical_with_unscoped_last() {
+        let commits = vec![
+            commit("fix: close the socket"),
+            commit("feat(zeta): add a button"),
+            commit("fix(alpha): handle nulls"),
+            commit("Update README"),
+            commit("docs(zeta): explain the button"),
+        ];
+        let sections = sections(commits, GroupBy::Scope, &now());
+        let titles = titles(&sections);
+
+        assert_eq!(titles.len(), 3);
+        assert!(titles[0].ends_with("alpha"));
+        assert!(titles[1].ends_with("zeta"));
+        assert_eq!(titles[2], "other");
+        assert_eq!(sections[1].1.len(), 2);
+        assert_eq!(sections[2].1.len(), 2);
+    }
+
+    #[test]
+    fn sections_by_author_put_the_busiest_first() {
+        let by = |name: &str| Commit {
+            author_name: name.to_string(),
+            ..commit("fix: thing")
+        };
+        let commits = vec![by("Carol"), by("Bob"), by("Alice"), by("Bob"), by("Dave"), by("Carol")];
+
+        assert_eq!(
+            titles(&sections(commits, GroupBy::Author, &now())),
+            ["Bob", "Carol", "Alice", "Dave"]
+        );
+    }
+
+    #[test]
+    fn sections_by_week_and_month() {
+        let commits = || {
+            vec![
+                Commit::fixture("feat: tuesday", "2025-02-04T12:00:00[UTC]"),
+                Commit::fixture("fix: monday", "2025-02-03T12:00:00[UTC]"),
+                Commit::fixture("fix: friday", "2025-01-31T12:00:00[UTC]"),
+                Commit::fixture("chore: new year's eve", "2024-12-31T12:00:00[UTC]"),
+            ]
+        };
+
+        assert_eq!(
+            titles(&sections(commits(), GroupBy::Week, &now())),
+            [
+                "Week of Monday, 3 February",
+                "Week of Monday, 27 January",
+                "Week of Monday, 30 December 2024"
+            ]
+        );
+        assert_eq!(
+            titles(&sections(commits(), GroupBy::Month, &now())),
+            ["February 2025", "January 2025", "December 2024"]
+        );
+    }
+
+    fn counted(counts: &[Count]) -> Vec<(&str, usize)> {
+        counts
+            .iter()
+            .map(|count| (count.name.as_str(), count.commits))
+            .collect()
+    }
+
+    #[test]
+    fn stats_count_authors_types_scopes_and_days() {
+        let by = |name: &str, message: &str, timestamp: &str| Commit {
+            author_name: name.to_string(),
+            stats: Some(crate::commit::DiffStats {
+                files: 1,
+                insertions: 10,
+                deletions: 2,
+            }),
+            ..Commit::fixture(message, timestamp)
+        };
+        let commits = [
+            by("Bob", "feat(ui): add a button", "2025-02-04T12:00:00[UTC]"),
+            by("Alice", "fix(ui): align it", "2025-02-04T15:00:00[UTC]"),
+            by("Bob", "fix: close the socket", "2025-02-03T12:00:00[UTC]"),
+            by("Bob", "Update README", "2025-01-31T12:00:00[UTC]"),
+        ];
+        let stats = Stats::new(&commits, &now());
+
+        assert_eq!(stats.commits, 4);
+        assert_eq!((stats.insertions, stats.deletions), (40, 8));
+        assert_eq!(stats.active_days, 3);
+        assert_eq!(counted(&stats.authors), [("Bob", 3), ("Alice", 1)]);
+        assert_eq!(counted(&stats.types), [("fix", 2), ("feat", 1)]);
+        assert_eq!(counted(&stats.scopes), [("ui", 2)]);
+        assert_eq!(
+            stats.busiest_day.map(|day| (day.name, day.commits)),
+            Some(("Tuesday, 4 February 2025".to_string(), 2))
+        );
+    }
+
+    #[test]
+    fn heatmap_counts_each_day_from_the_first_monday() {
+        let commits = [
+            Commit::fixture("feat: tuesday", "2025-02-04T12:00:00[UTC]"),
+            Commit::fixture("fix: tuesday", "2025-02-04T15:00:00[UTC]"),
+            Commit::fixture("fix: friday", "2025-01-31T12:00:00[UTC]"),
+            Commit::fixture("chore: too old", "2025-01-19T12:00:00[UTC]"),
+        ];
+        let heatmap = Heatmap::new(&commits, 3, &now()).unwrap();
+
+        assert_eq!(Heatmap::start(3, &now()).unwrap().date(), Date::constant(2025, 1, 20));
+        assert_eq!(heatmap.commits, 3);
+        assert_eq!(heatmap.days.len(), 17);
+        assert_eq!(heatmap.days.first().unwrap().name, "2025-01-20");
+        assert_eq!(heatmap.days.last().unwrap().name, "2025-02-05");
+        assert_eq!(
+            counted(&heatmap.days)
+                .into_iter()
+                .filter(|(_, commits)| *commits > 0)
+                .collect::<Vec<_>>(),
+            [("2025-01-31", 1), ("2025-02-04", 2)]
+        );
+        assert_eq!(heatmap.months(), "Jan Feb");
+    }
+
+    #[test]
+    fn bar_is_drawn_to_an_eighth_of_a_column() {
+        assert_eq!(bar(4, 4), "█".repeat(30));
+        assert_eq!(bar(1, 8), "███▊");
+        assert_eq!(bar(1, 1000), "▏");
+        assert_eq!(bar(0, 4), "");
+    }
+
+    #[test]
+    fn table_aligns_columns_and_charts_counts() {
+        let (bob, alice) = (
+            Count {
+                name: "Bob".to_string(),
+                commits: 3,
+            },
+            Count {
+                name: "Alice".to_string(),
+                commits: 1,
+            },
+        );
+        let rows = [("🥇".to_string(), &bob), ("🥈".to_string(), &alice)];
+
+        assert_eq!(
+            table("  ", &rows, 4, false),
+            "  🥇 Bob    3   75%\n  🥈 Alice  1   25%\n"
+        );
+        assert_eq!(
+            table("  ", &rows, 4, true),
+            format!(
+                "  🥇 Bob    3   75%  {}\n  🥈 Alice  1   25%  {}\n",
+                "█".repeat(30),
+                "█".repeat(10)
+            )
+        );
+    }
+
+    #[test]
+    fn tally_summary_totals_commits_authors_days_and_types() {
+        let (now, printer) = (now(), SpanPrinter::new());
+        let output = Output::new(Format::Markdown, &now, &printer);
+        let bob = Commit {
+            author_name: "Bob".to_string(),
+            ..Commit::fixture("fix: close the socket", "2025-02-03T12:00:00[UTC]")
+        };
+        let commits = [
+            commit("chore: bump deps"),
+            commit("fix: handle nulls"),
+            bob,
+            commit("feat: add a button"),
+            commit("Update README"),
+        ];
+
+        assert_eq!(Tally::new(&now).summary(&output), None);
+        assert_eq!(
+            Tally::of(&commits, &now).summary(&output).unwrap(),
+            "— 5 commits by 2 authors over 2 days (1 feat, 2 fix, 1 chore)"
+        );
+        assert_eq!(
+            Tally::of(&commits[..1], &now).summary(&output).unwrap(),
+            "— 1 commit by 1 author over 1 day (1 chore)"
+        );
+    }
+
+    #[test]
+    fn release_notes_end_with_a_compare_link() {
+        let linked = |message: &str| Commit {
+            url: Some("https://github.com/acme/widget".to_string()),
+            ..commit(message)
+        };
+        let merge = Commit {
+            parent_count: 2,
+            ..linked("Merge pull request #3 from bob/topic")
+        };
+        let commits = vec![
+            linked("fix: close the socket"),
+            merge,
+            linked("Update README"),
+            linked("feat: add a button"),
+        ];
+        let notes = release_notes(commits, "v1.0.0", "v1.1.0");
+        let titles: Vec<_> = notes.lines().filter(|line| line.starts_with("### ")).collect();
+
+        assert!(notes.starts_with("### "));
+        assert_eq!(titles.len(), 2);
+        assert!(titles[0].ends_with("Features"));
+        assert!(titles[1].ends_with("Fixes"));
+        assert!(!notes.contains("README") && !notes.contains("Merge"));
+        assert!(notes.ends_with("\n**Full Changelog**: https://github.com/acme/widget/compare/v1.0.0...v1.1.0\n"));
+    }
+}
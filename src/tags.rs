@@ -0,0 +1,70 @@
+use git2::{Oid, Repository};
+use semver::Version;
+
+/// A tag whose name parses as a semantic version, such as `v1.2.0` or `my-app/1.2.0`.
+pub struct Release {
+    pub name: String,
+    pub oid: Oid,
+    pub version: Version,
+}
+
+/// Every semver-like tag in the repository, highest version first.
+pub fn releases(repo: &Repository) -> Result<Vec<Release>, git2::Error> {
+    //
+    let mut releases = Vec::new();
+
+    for name in repo.tag_names(None)?.iter().flatten() {
+        let Some(version) = parse_version(name) else {
+            continue;
+        };
+
+        // Annotated and lightweight tags both peel to the tagged commit.
+        if let Ok(commit) = repo
+            .revparse_single(&format!("refs/tags/{name}"))
+            .and_then(|obj| obj.peel_to_commit())
+        {
+            releases.push(Release {
+                name: name.to_string(),
+                oid: commit.id(),
+                version,
+            });
+        }
+    }
+
+    releases.sort_by(|a, b| b.version.cmp(&a.version));
+
+    Ok(releases)
+}
+
+/// The release tagged with a version, such as `1.2.0` for a `v1.2.0` tag.
+pub fn release(repo: &Repository, version: &str) -> Result<Option<Release>, git2::Error> {
+    //
+    let Some(version) = parse_version(version) else {
+        return Ok(None);
+    };
+
+    Ok(releases(repo)?.into_iter().find(|release| release.version == version))
+}
+
+/// The highest versioned release reachable from the given commit.
+pub fn latest_release(repo: &Repository, from: Oid) -> Result<Option<Release>, git2::Error> {
+    Ok(releases(repo)?
+        .into_iter()
+        .find(|release| release.oid == from || repo.graph_descendant_of(from, release.oid).unwrap_or_default()))
+}
+
+/// The earliest release containing the given commit, or `None` if it is unreleased.
+pub fn containing_release<'a>(repo: &Repository, releases: &'a [Release], oid: Oid) -> Option<&'a Release> {
+    releases
+        .iter()
+        .rev()
+        .find(|release| release.oid == oid || repo.graph_descendant_of(release.oid, oid).unwrap_or_default())
+}
+
+/// Parse the version out of a tag name, ignoring any `package/` prefix and a leading `v`.
+#[must_use]
+pub fn parse_version(name: &str) -> Option<Version> {
+    let name = name.rsplit('/').next().unwrap_or(name);
+
+    Version::parse(name.strip_prefix('v').unwrap_or(name)).ok()
+}